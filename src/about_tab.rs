@@ -4,7 +4,7 @@ use ratatui::widgets::Paragraph;
 
 use crate::tabs::AppTab;
 use crate::msg::Message;
-use crate::colors::BG_MAIN_COLOR;
+use crate::colors::theme;
 
 
 pub static LOGO: &str = include_str!("../assets/logo.txt");
@@ -17,7 +17,7 @@ impl AboutTab {
 
     fn title_paragraph(&mut self) -> Paragraph<'_> {
         Paragraph::new(LOGO)
-            .bg(BG_MAIN_COLOR)
+            .bg(theme().main_background)
             .centered()
     }
 
@@ -39,17 +39,23 @@ pub fn layout(area: Rect) -> Vec<Rect> {
     ])
     .split(area);
 
-    layout[1..]
-        .iter()
-        .copied()
-        .collect()
+    layout[1..].to_vec()
 }
 
 impl AppTab for AboutTab {
-    fn handle_message(&mut self, message: Message) {
-        match message {
-            _ => (),
-        }
+    fn id(&self) -> &'static str {
+        "about"
+    }
+
+    fn title(&self) -> &'static str {
+        "About"
+    }
+
+    fn icon(&self) -> &'static str {
+        "❔"
+    }
+
+    fn handle_message(&mut self, _message: Message) {
     }
     
     fn render_paragraph(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
@@ -59,7 +65,7 @@ impl AppTab for AboutTab {
         Ok(())
     }
 
-    fn help_text(&mut self) -> Vec<Span> {
+    fn help_entries(&self) -> Vec<(Message, &'static str)> {
         vec![]
     }
 }
@@ -0,0 +1,284 @@
+use std::fmt;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Datelike, Days, LocalResult, NaiveDate, NaiveTime, TimeDelta, TimeZone, Timelike, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::LocalZone;
+
+
+/// On which days an alarm rings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Repeat {
+    /// Rings the next time its time comes round, then turns itself off
+    Once,
+    Daily,
+    /// Monday to Friday
+    Weekdays,
+    Days(Vec<Weekday>),
+}
+
+impl Repeat {
+    fn matches(&self, weekday: Weekday) -> bool {
+        match self {
+            Repeat::Once | Repeat::Daily => true,
+            Repeat::Weekdays => !matches!(weekday, Weekday::Sat | Weekday::Sun),
+            Repeat::Days(days) => days.contains(&weekday),
+        }
+    }
+
+    /// Parses `once`, `daily`, `weekdays`, `weekends` or a list of days such as `mon,wed,fri`.
+    fn parse(word: &str) -> Option<Self> {
+        let repeat = match word.to_ascii_lowercase().as_str() {
+            "once" => Repeat::Once,
+            "daily" | "everyday" => Repeat::Daily,
+            "weekdays" => Repeat::Weekdays,
+            "weekends" => Repeat::Days(vec![Weekday::Sat, Weekday::Sun]),
+            list => {
+                let mut days = list
+                    .split(',')
+                    .map(|day| day.parse::<Weekday>().ok())
+                    .collect::<Option<Vec<_>>>()?;
+                days.sort_by_key(Weekday::num_days_from_monday);
+                days.dedup();
+                Repeat::Days(days)
+            }
+        };
+        Some(repeat)
+    }
+}
+
+impl fmt::Display for Repeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Repeat::Once => write!(f, "once"),
+            Repeat::Daily => write!(f, "daily"),
+            Repeat::Weekdays => write!(f, "weekdays"),
+            Repeat::Days(days) => {
+                let days: Vec<String> = days.iter().map(|day| day.to_string().to_lowercase()).collect();
+                write!(f, "{}", days.join(","))
+            }
+        }
+    }
+}
+
+/// A wall-clock alarm in the local time zone, which is passed in as `zone`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alarm {
+    pub label: String,
+    pub time: NaiveTime,
+    pub repeat: Repeat,
+    pub enabled: bool,
+    /// When the alarm rings next, `None` while it is off
+    pub next: Option<DateTime<Utc>>,
+}
+
+impl Alarm {
+    /// Parses `<time> [repeat] [label]`, e.g. `07:00 weekdays Wake up` or `18:30 Pizza`.
+    /// Without a repeat the alarm rings once.
+    pub fn parse(spec: &str, now: DateTime<Utc>, zone: LocalZone) -> Result<Self> {
+        let mut words = spec.split_whitespace().peekable();
+        let Some(time) = words.next() else {
+            bail!("missing time, e.g. 07:00 weekdays Wake up");
+        };
+        let time = NaiveTime::parse_from_str(time, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+            .map_err(|_| anyhow::anyhow!("invalid time '{time}', expected hh:mm"))?;
+
+        let repeat = words.peek().and_then(|word| Repeat::parse(word));
+        if repeat.is_some() {
+            words.next();
+        }
+        let label = words.collect::<Vec<_>>().join(" ");
+
+        let mut alarm = Self {
+            label: if label.is_empty() { String::from("Alarm") } else { label },
+            time,
+            repeat: repeat.unwrap_or(Repeat::Once),
+            enabled: true,
+            next: None,
+        };
+        alarm.schedule(now, zone);
+        Ok(alarm)
+    }
+
+    /// The text `parse` reads back into this alarm, used to edit it.
+    pub fn spec(&self) -> String {
+        format!("{} {} {}", self.time_text(), self.repeat, self.label)
+    }
+
+    /// The time of day as `hh:mm`, with seconds only when they are set.
+    pub fn time_text(&self) -> String {
+        if self.time.second() == 0 {
+            self.time.format("%H:%M").to_string()
+        } else {
+            self.time.format("%H:%M:%S").to_string()
+        }
+    }
+
+    /// Sets `next` to the first ring after `now`, or clears it when the alarm is off.
+    pub fn schedule(&mut self, now: DateTime<Utc>, zone: LocalZone) {
+        self.next = if self.enabled { self.next_after(now, zone) } else { None };
+    }
+
+    fn next_after(&self, now: DateTime<Utc>, zone: LocalZone) -> Option<DateTime<Utc>> {
+        let today = now.with_timezone(&zone).date_naive();
+        (0..=7)
+            .filter_map(|ahead| today.checked_add_days(Days::new(ahead)))
+            .filter(|date| self.repeat.matches(date.weekday()))
+            .map(|date| local_to_utc(zone, date, self.time))
+            .find(|at| *at > now)
+    }
+
+    /// Moves on after ringing: a one-shot alarm turns itself off, the others are rescheduled.
+    pub fn rang(&mut self, now: DateTime<Utc>, zone: LocalZone) {
+        if self.repeat == Repeat::Once {
+            self.enabled = false;
+        }
+        self.schedule(now, zone);
+    }
+}
+
+/// Resolves a local date and time. A time skipped when the clocks go forward rings an hour
+/// later, one that occurs twice when they go back rings the first time.
+fn local_to_utc(zone: LocalZone, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    let local = date.and_time(time);
+    match zone.from_local_datetime(&local) {
+        LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => at.with_timezone(&Utc),
+        LocalResult::None => zone
+            .from_local_datetime(&(local + TimeDelta::hours(1)))
+            .earliest()
+            .map_or_else(|| local.and_utc(), |at| at.with_timezone(&Utc)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::{Europe, Tz};
+
+    use super::*;
+
+    const UTC: LocalZone = LocalZone::Fixed(Tz::UTC);
+
+    /// Saturday, 2026-03-14 09:00 UTC.
+    fn now() -> DateTime<Utc> {
+        utc(2026, 3, 14, 9, 0)
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn alarm(spec: &str) -> Alarm {
+        Alarm::parse(spec, now(), UTC).unwrap_or_else(|err| panic!("{spec}: {err}"))
+    }
+
+    #[test]
+    fn parses_time_repeat_and_label() {
+        for (spec, expected_time, expected_repeat, expected_label) in [
+            ("07:00", time(7, 0), Repeat::Once, "Alarm"),
+            ("07:00 weekdays Wake up", time(7, 0), Repeat::Weekdays, "Wake up"),
+            ("18:30 Pizza", time(18, 30), Repeat::Once, "Pizza"),
+            ("18:30 DAILY", time(18, 30), Repeat::Daily, "Alarm"),
+            ("06:15:30 everyday", NaiveTime::from_hms_opt(6, 15, 30).unwrap(), Repeat::Daily, "Alarm"),
+            ("10:00 weekends Brunch", time(10, 0), Repeat::Days(vec![Weekday::Sat, Weekday::Sun]), "Brunch"),
+            ("08:00 fri,mon,wed,mon Gym", time(8, 0), Repeat::Days(vec![Weekday::Mon, Weekday::Wed, Weekday::Fri]), "Gym"),
+            ("08:00 once   spaced  out ", time(8, 0), Repeat::Once, "spaced out"),
+        ] {
+            let alarm = alarm(spec);
+            assert_eq!(alarm.time, expected_time, "{spec}");
+            assert_eq!(alarm.repeat, expected_repeat, "{spec}");
+            assert_eq!(alarm.label, expected_label, "{spec}");
+            assert!(alarm.enabled, "{spec}");
+        }
+    }
+
+    #[test]
+    fn spec_parses_back_into_the_same_alarm() {
+        for spec in ["07:00 weekdays Wake up", "06:15:30 daily Alarm", "10:00 sat,sun Brunch"] {
+            let alarm = alarm(spec);
+            assert_eq!(alarm.spec(), spec);
+            assert_eq!(Alarm::parse(&alarm.spec(), now(), UTC).unwrap(), alarm, "{spec}");
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        for (spec, expected) in [
+            ("", "missing time, e.g. 07:00 weekdays Wake up"),
+            ("   ", "missing time, e.g. 07:00 weekdays Wake up"),
+            ("7am Wake up", "invalid time '7am', expected hh:mm"),
+            ("25:00", "invalid time '25:00', expected hh:mm"),
+        ] {
+            assert_eq!(Alarm::parse(spec, now(), UTC).unwrap_err().to_string(), expected, "{spec:?}");
+        }
+    }
+
+    #[test]
+    fn next_ring_after_now() {
+        for (spec, expected) in [
+            ("17:30", utc(2026, 3, 14, 17, 30)),
+            // the current minute has already started, so it is tomorrow
+            ("09:00", utc(2026, 3, 15, 9, 0)),
+            ("08:00 daily", utc(2026, 3, 15, 8, 0)),
+            // a Saturday, so Monday
+            ("07:00 weekdays", utc(2026, 3, 16, 7, 0)),
+            ("10:00 weekends", utc(2026, 3, 14, 10, 0)),
+            ("08:00 sat", utc(2026, 3, 21, 8, 0)),
+            ("08:00 fri", utc(2026, 3, 20, 8, 0)),
+        ] {
+            assert_eq!(alarm(spec).next, Some(expected), "{spec}");
+        }
+    }
+
+    #[test]
+    fn rang_turns_a_one_shot_off_and_reschedules_the_others() {
+        let mut once = alarm("09:30");
+        once.rang(utc(2026, 3, 14, 9, 30), UTC);
+        assert!(!once.enabled);
+        assert_eq!(once.next, None);
+
+        let mut daily = alarm("09:30 daily");
+        daily.rang(utc(2026, 3, 14, 9, 30), UTC);
+        assert!(daily.enabled);
+        assert_eq!(daily.next, Some(utc(2026, 3, 15, 9, 30)));
+    }
+
+    #[test]
+    fn local_times_across_daylight_saving() {
+        let berlin = LocalZone::Fixed(Europe::Berlin);
+        let date = |month, day| NaiveDate::from_ymd_opt(2026, month, day).unwrap();
+        for (date, time, expected) in [
+            // winter and summer time
+            (date(3, 28), time(2, 30), utc(2026, 3, 28, 1, 30)),
+            (date(3, 30), time(2, 30), utc(2026, 3, 30, 0, 30)),
+            // skipped when the clocks go forward, so an hour later
+            (date(3, 29), time(2, 30), utc(2026, 3, 29, 1, 30)),
+            (date(3, 29), time(3, 0), utc(2026, 3, 29, 1, 0)),
+            // twice when the clocks go back, so the first time
+            (date(10, 25), time(2, 30), utc(2026, 10, 25, 0, 30)),
+            (date(10, 25), time(3, 0), utc(2026, 10, 25, 2, 0)),
+        ] {
+            assert_eq!(local_to_utc(berlin, date, time), expected, "{date} {time}");
+        }
+    }
+
+    #[test]
+    fn daily_alarm_keeps_its_local_time_across_daylight_saving() {
+        let berlin = LocalZone::Fixed(Europe::Berlin);
+        let mut alarm = Alarm::parse("07:00 daily", utc(2026, 3, 28, 7, 0), berlin).unwrap();
+        assert_eq!(alarm.next, Some(utc(2026, 3, 29, 5, 0)));
+        alarm.rang(utc(2026, 3, 29, 5, 0), berlin);
+        assert_eq!(alarm.next, Some(utc(2026, 3, 30, 5, 0)));
+
+        let mut alarm = Alarm::parse("02:30 daily", utc(2026, 3, 28, 12, 0), berlin).unwrap();
+        assert_eq!(alarm.next, Some(utc(2026, 3, 29, 1, 30)));
+        alarm.rang(utc(2026, 3, 29, 1, 30), berlin);
+        assert_eq!(alarm.next, Some(utc(2026, 3, 30, 0, 30)));
+    }
+}
@@ -0,0 +1,508 @@
+use anyhow::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::alarm::Alarm;
+use crate::clock::SharedClock;
+use crate::colors::theme;
+use crate::config::{Config, NotificationConfig};
+use crate::hooks::{HookEvent, HookKind};
+use crate::keymap::TextInput;
+use crate::msg::Message;
+use crate::states::AppState;
+use crate::tabs::{AppTab, TabStatus, TimerState};
+use crate::ui::setup_big_timer_text;
+
+
+/// How late an alarm may be noticed, e.g. after the app was closed, and still ring.
+const MISSED_GRACE: TimeDelta = TimeDelta::minutes(10);
+
+/// An alarm that went off and waits to be dismissed or snoozed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Ringing {
+    label: String,
+    time: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedAlarms {
+    alarms: Vec<Alarm>,
+    #[serde(default)]
+    ringing: Vec<Ringing>,
+    #[serde(default)]
+    snoozed: Vec<(DateTime<Utc>, Ringing)>,
+}
+
+/// An alarm being typed as `<time> [repeat] [label]`, new or replacing the selected one.
+#[derive(Debug, Clone, PartialEq)]
+struct AlarmInput {
+    text: String,
+    editing: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AlarmTab {
+    alarms: Vec<Alarm>,
+    selected: usize,
+    input: Option<AlarmInput>,
+    /// Alarms that went off, the first one is shown until it is dismissed or snoozed
+    ringing: Vec<Ringing>,
+    snoozed: Vec<(DateTime<Utc>, Ringing)>,
+    snooze: std::time::Duration,
+    notification: NotificationConfig,
+    hook_events: Vec<HookEvent>,
+    /// Alarms missed while the app was running, e.g. during a suspend
+    notices: Vec<String>,
+    clock: SharedClock,
+}
+
+impl AlarmTab {
+
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            clock,
+            ..Self::default()
+        }
+    }
+
+    fn new_alarm(&mut self) {
+        self.input = Some(AlarmInput {
+            text: String::new(),
+            editing: false,
+        });
+    }
+
+    fn edit(&mut self) {
+        if let Some(alarm) = self.alarms.get(self.selected) {
+            self.input = Some(AlarmInput {
+                text: alarm.spec(),
+                editing: true,
+            });
+        }
+    }
+
+    fn parsed_input(&self) -> Option<Result<Alarm>> {
+        self.input.as_ref().map(|input| Alarm::parse(&input.text, self.clock.utc(), self.clock.zone()))
+    }
+
+    fn confirm_input(&mut self) {
+        let Some(Ok(alarm)) = self.parsed_input() else {
+            return;
+        };
+
+        if self.input.take().is_some_and(|input| input.editing) {
+            self.alarms[self.selected] = alarm;
+        } else {
+            self.alarms.push(alarm);
+        }
+        self.sort_alarms();
+    }
+
+    /// Keeps alarms ordered by time of day, with the selection following the same alarm.
+    fn sort_alarms(&mut self) {
+        let selected = self.alarms.get(self.selected).cloned();
+        self.alarms.sort_by_key(|alarm| alarm.time);
+        self.selected = selected
+            .and_then(|selected| self.alarms.iter().position(|alarm| *alarm == selected))
+            .unwrap_or(0);
+    }
+
+    fn input_char(&mut self, c: char) {
+        if let Some(input) = self.input.as_mut() {
+            if !c.is_control() {
+                input.text.push(c);
+            }
+        }
+    }
+
+    fn input_backspace(&mut self) {
+        if let Some(input) = self.input.as_mut() {
+            input.text.pop();
+        }
+    }
+
+    fn toggle_enabled(&mut self) {
+        if let Some(alarm) = self.alarms.get_mut(self.selected) {
+            alarm.enabled = !alarm.enabled;
+            alarm.schedule(self.clock.utc(), self.clock.zone());
+        }
+    }
+
+    fn remove(&mut self) {
+        if self.selected < self.alarms.len() {
+            self.alarms.remove(self.selected);
+            self.selected = self.selected.min(self.alarms.len().saturating_sub(1));
+        }
+    }
+
+    fn select(&mut self, forward: bool) {
+        let count = self.alarms.len();
+        if count == 0 {
+            return;
+        }
+
+        self.selected = if forward {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+    }
+
+    fn dismiss(&mut self) {
+        if !self.ringing.is_empty() {
+            self.ringing.remove(0);
+        }
+    }
+
+    fn snooze(&mut self) {
+        if self.ringing.is_empty() {
+            return;
+        }
+
+        let ringing = self.ringing.remove(0);
+        let until = self.clock.utc() + TimeDelta::from_std(self.snooze).unwrap_or_default();
+        self.snoozed.push((until, ringing));
+    }
+
+    fn ring(&mut self, ringing: Ringing) {
+        self.notification.show(&format!("{} ({})", ringing.label, ringing.time));
+        self.hook_events.push(HookEvent::new(HookKind::Finish, ringing.label.as_str()));
+        self.ringing.push(ringing);
+    }
+
+    /// Rings every alarm and snooze that is due. Alarms noticed more than `MISSED_GRACE` late
+    /// are only rescheduled and returned, so a long-missed alarm does not go off out of the blue.
+    fn check_alarms(&mut self) -> Vec<String> {
+        let now = self.clock.utc();
+        let mut missed = vec![];
+
+        for index in 0..self.alarms.len() {
+            let alarm = &mut self.alarms[index];
+            let Some(next) = alarm.next.filter(|next| *next <= now) else {
+                continue;
+            };
+
+            let ringing = Ringing {
+                label: alarm.label.clone(),
+                time: alarm.time_text(),
+            };
+            alarm.rang(now, self.clock.zone());
+
+            if now - next > MISSED_GRACE {
+                missed.push(format!(
+                    "Missed alarm '{}' at {}",
+                    ringing.label,
+                    next.with_timezone(&self.clock.zone()).format("%a %H:%M")
+                ));
+            } else {
+                self.ring(ringing);
+            }
+        }
+
+        let (due, waiting) = self.snoozed.drain(..).partition(|(until, _)| *until <= now);
+        self.snoozed = waiting;
+        for (_, ringing) in due {
+            self.ring(ringing);
+        }
+
+        missed
+    }
+
+    fn alarms_table(&self) -> Table<'_> {
+        let rows = self.alarms.iter().map(|alarm| {
+            let next = match alarm.next {
+                Some(next) => next.with_timezone(&self.clock.zone()).format("%a %d %b %H:%M").to_string(),
+                None => String::from("off"),
+            };
+            let style = if alarm.enabled { Style::new() } else { Style::new().dim() };
+
+            Row::new(vec![
+                Cell::from(alarm.time_text()),
+                Cell::from(alarm.repeat.to_string()),
+                Cell::from(alarm.label.as_str()),
+                Cell::from(next),
+            ])
+            .style(style)
+        });
+
+        let widths = [
+            Constraint::Length(8),
+            Constraint::Length(27),
+            Constraint::Min(12),
+            Constraint::Length(16),
+        ];
+
+        Table::new(rows, widths)
+            .header(Row::new(vec!["Time", "Repeat", "Label", "Next"]).bold())
+            .column_spacing(2)
+            .highlight_style(Style::new().reversed())
+            .block(Block::default().borders(Borders::TOP).dim())
+            .bg(theme().main_background)
+    }
+
+    fn input_paragraph(&self) -> Paragraph<'_> {
+        let Some(input) = &self.input else {
+            let text = match self.alarms.len() {
+                0 => String::from("No alarms yet"),
+                _ => format!("{} of {} alarms on", self.alarms.iter().filter(|a| a.enabled).count(), self.alarms.len()),
+            };
+            return Paragraph::new(text).dim().centered();
+        };
+
+        Paragraph::new(Line::from(vec![
+            "Alarm: ".dim(),
+            input.text.as_str().into(),
+            " ".reversed(),
+        ]))
+        .centered()
+    }
+
+    fn validation_paragraph(&self) -> Paragraph<'_> {
+        let line = match self.parsed_input() {
+            None => Line::default(),
+            Some(_) if self.input.as_ref().is_some_and(|input| input.text.trim().is_empty()) => {
+                "e.g. 07:00 weekdays Wake up, 18:30 Pizza or 09:00 mon,thu Gym".dim().into()
+            }
+            Some(Ok(alarm)) => match alarm.next {
+                Some(next) => format!("= {} {}, next {}", alarm.repeat, alarm.label, next.with_timezone(&self.clock.zone()).format("%a %d %b %H:%M"))
+                    .green()
+                    .into(),
+                None => "never rings".red().into(),
+            },
+            Some(Err(err)) => err.to_string().red().into(),
+        };
+
+        Paragraph::new(line).centered()
+    }
+
+}
+
+pub fn layout(area: Rect) -> Vec<Rect> {
+    let layout = Layout::vertical(vec![
+        Constraint::Length(1), // INGORE
+        Constraint::Length(1), // input
+        Constraint::Length(1), // validation
+        Constraint::Min(0), // alarms
+    ])
+    .split(area);
+
+    let table = Layout::horizontal(vec![
+        Constraint::Percentage(10),
+        Constraint::Percentage(80),
+        Constraint::Percentage(10),
+    ])
+    .split(layout[3]);
+
+    vec![layout[1], layout[2], table[1]]
+}
+
+pub fn modal_layout(area: Rect) -> Vec<Rect> {
+    let layout = Layout::vertical(vec![
+        Constraint::Percentage(100), // INGORE
+        Constraint::Length(1), // label
+        Constraint::Min(8), // time
+        Constraint::Length(1), // more alarms
+        Constraint::Percentage(100), // INGORE
+    ])
+    .split(area);
+
+    layout[1..4].to_vec()
+}
+
+impl AppTab for AlarmTab {
+    fn id(&self) -> &'static str {
+        "alarms"
+    }
+
+    fn title(&self) -> &'static str {
+        "Alarms"
+    }
+
+    fn icon(&self) -> &'static str {
+        "⏰"
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        if !self.ringing.is_empty() {
+            match message {
+                Message::Dismiss => self.dismiss(),
+                Message::Snooze => self.snooze(),
+                _ => (),
+            }
+            return;
+        }
+
+        if self.input.is_some() {
+            match message {
+                Message::Input(c) => self.input_char(c),
+                Message::Backspace => self.input_backspace(),
+                Message::ToggleStartPause => self.confirm_input(),
+                Message::Cancel => self.input = None,
+                _ => (),
+            }
+            return;
+        }
+
+        match message {
+            Message::New => self.new_alarm(),
+            Message::Edit => self.edit(),
+            Message::ToggleStartPause => self.toggle_enabled(),
+            Message::Remove => self.remove(),
+            Message::ScrollUp => self.select(false),
+            Message::ScrollDown => self.select(true),
+            _ => (),
+        }
+    }
+
+    fn tick(&mut self) {
+        // a running app misses alarms too when the computer sleeps or the app is stopped
+        let missed = self.check_alarms();
+        if !missed.is_empty() {
+            self.notices.push(missed.join(" | "));
+        }
+    }
+
+    fn apply_config(&mut self, config: &Config) {
+        self.snooze = config.alarms.snooze;
+        self.notification = config.notification.clone();
+    }
+
+    fn render_paragraph(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
+        let area = layout(*area);
+        frame.render_widget(self.input_paragraph(), area[0]);
+        frame.render_widget(self.validation_paragraph(), area[1]);
+
+        let mut table_state = TableState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(self.alarms_table(), area[2], &mut table_state);
+        Ok(())
+    }
+
+    fn has_modal(&self) -> bool {
+        !self.ringing.is_empty()
+    }
+
+    fn render_modal(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
+        let Some(ringing) = self.ringing.first() else {
+            return Ok(());
+        };
+
+        frame.render_widget(Clear, *area);
+        frame.render_widget(Block::default().bg(theme().main_background), *area);
+
+        let layout = modal_layout(*area);
+        frame.render_widget(Paragraph::new(ringing.label.as_str()).bold().centered(), layout[0]);
+        frame.render_widget(
+            setup_big_timer_text(ringing.time.clone(), AppState::Running, area.width),
+            layout[1],
+        );
+
+        let more = match self.ringing.len() - 1 {
+            0 => String::new(),
+            1 => String::from("1 more alarm"),
+            count => format!("{count} more alarms"),
+        };
+        frame.render_widget(Paragraph::new(more).dim().centered(), layout[2]);
+        Ok(())
+    }
+
+    /// The ringing alarm, or else the next one to go off, snoozed ones included.
+    fn status(&self) -> Option<TabStatus> {
+        if let Some(ringing) = self.ringing.first() {
+            return Some(TabStatus {
+                state: TimerState::Ringing,
+                label: ringing.label.clone(),
+                elapsed: None,
+                remaining: None,
+            });
+        }
+
+        let scheduled = self.alarms.iter().filter_map(|alarm| Some((alarm.next?, alarm.label.as_str())));
+        let snoozed = self.snoozed.iter().map(|(until, ringing)| (*until, ringing.label.as_str()));
+        let status = match scheduled.chain(snoozed).min_by_key(|(at, _)| *at) {
+            Some((at, label)) => TabStatus {
+                state: TimerState::Armed,
+                label: label.to_string(),
+                elapsed: None,
+                remaining: Some((at - self.clock.utc()).to_std().unwrap_or_default()),
+            },
+            None => TabStatus {
+                state: TimerState::Idle,
+                label: String::new(),
+                elapsed: None,
+                remaining: None,
+            },
+        };
+        Some(status)
+    }
+
+    fn save_state(&self) -> Option<Value> {
+        let saved = SavedAlarms {
+            alarms: self.alarms.clone(),
+            ringing: self.ringing.clone(),
+            snoozed: self.snoozed.clone(),
+        };
+        serde_json::to_value(saved).ok()
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<Option<String>> {
+        let saved: SavedAlarms = serde_json::from_value(state)?;
+        self.alarms = saved.alarms;
+        self.ringing = saved.ringing;
+        self.snoozed = saved.snoozed;
+        self.sort_alarms();
+
+        let missed = self.check_alarms();
+        Ok((!missed.is_empty()).then(|| missed.join(" | ")))
+    }
+
+    fn take_hook_events(&mut self) -> Vec<HookEvent> {
+        std::mem::take(&mut self.hook_events)
+    }
+
+    fn take_notices(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notices)
+    }
+
+    fn help_entries(&self) -> Vec<(Message, &'static str)> {
+        if !self.ringing.is_empty() {
+            return vec![
+                (Message::Dismiss, "Dismiss"),
+                (Message::Snooze, "Snooze"),
+            ];
+        }
+
+        if self.input.is_some() {
+            return vec![
+                (Message::ToggleStartPause, "Save"),
+                (Message::Cancel, "Cancel"),
+            ];
+        }
+
+        let mut entries = vec![(Message::New, "New Alarm")];
+
+        if let Some(alarm) = self.alarms.get(self.selected) {
+            entries.push((Message::ToggleStartPause, if alarm.enabled { "Turn Off" } else { "Turn On" }));
+            entries.push((Message::Edit, "Edit"));
+            entries.push((Message::Remove, "Remove"));
+        }
+
+        if self.alarms.len() > 1 {
+            entries.push((Message::ScrollUp, "Select"));
+            entries.push((Message::ScrollDown, "Select"));
+        }
+
+        entries
+    }
+
+    fn text_input(&self) -> TextInput {
+        // a ringing alarm covers the input, and its keys must still snooze it
+        if self.input.is_some() && self.ringing.is_empty() {
+            TextInput::All
+        } else {
+            TextInput::Off
+        }
+    }
+}
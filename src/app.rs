@@ -1,57 +1,436 @@
+use std::collections::BTreeMap;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
 use ratatui::widgets::Paragraph;
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use futures::FutureExt;
 use ratatui::prelude::*;
+use tokio::select;
 
+use crate::cli::{Cli, Command};
+use crate::clock::SharedClock;
+use crate::config::Config;
+use crate::control::{ControlServer, Request, Response};
+use crate::duration::parse_duration;
 use crate::fps::FpsCounter;
+use crate::history;
+use crate::hooks::Hooks;
+use crate::keymap::{parse_action, Keymap};
 use crate::msg::Message;
+use crate::session::Session;
+use crate::signals::{Signal, Signals};
 use crate::splash::show_splash;
-use crate::tabs::TabGroup;
+use crate::tabs::{TabGroup, TabStatus};
+use crate::target::parse_target;
 use crate::ui::*;
 use crate::tui::Tui;
 use crate::eve::EventHandler;
-use crate::colors::BG_COLOR;
+use crate::watcher::FileWatcher;
+use crate::colors::{set_theme, theme};
 
 #[derive(Debug, Clone)]
 pub struct App {
     active: bool,
+    show_splash: bool,
     fps_counter: FpsCounter,
     tabs_group: TabGroup,
+    config: Config,
+    keymap: Keymap,
+    config_watcher: Option<FileWatcher>,
+    notice: Option<(String, Instant)>,
+    session_path: Option<PathBuf>,
+    history_path: Option<PathBuf>,
+    socket_path: Option<PathBuf>,
+    last_checkpoint: Instant,
+    /// Time source of the tabs and the event loop
+    clock: SharedClock,
+    /// Whether the screen is out of date
+    dirty: bool,
+    /// When the tabs on screen change on their own next, as of the last draw
+    redraw_at: Option<Instant>,
+    /// What was on screen when last drawn: the keymap context, and whether it was changing
+    drawn_view: (String, bool),
+    /// Set by `Message::Suspend`, as only the event loop has the terminal to hand back
+    suspending: bool,
+    /// When the tabs are ticked next. Input only ever brings it forward, so a steady stream
+    /// of keys or requests cannot hold off alarms, checkpoints and reloads.
+    next_tick: Instant,
 }
 
 impl App {
+    const NOTICE_DURATION: Duration = Duration::from_secs(5);
+    const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+    /// Longest wait between ticks, for notices, checkpoints and watched files
+    const MAX_TICK_INTERVAL: Duration = Duration::from_secs(1);
+    /// Shortest wait between ticks for tabs in the background, close enough for their
+    /// timers to end on time
+    const BACKGROUND_TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Builds the app from the config file and the command line, pre-seeding the tab
+    /// selected by `--tab` or a subcommand.
+    pub fn new(cli: &Cli) -> Result<Self> {
+        let config_path = cli.config.clone().or_else(Config::default_path);
+        let config = match &config_path {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+
+        let mut app = Self {
+            show_splash: config.splash && !cli.no_splash,
+            socket_path: Some(cli.socket_path()),
+            ..Default::default()
+        };
+        app.config_watcher = config_path.map(|path| FileWatcher::new(path, app.clock.clone()));
+        app.apply_config(config);
+
+        if let Some(tab) = &app.config.default_tab {
+            app.tabs_group.select(tab).context("invalid default_tab in config")?;
+        }
+
+        // a tab started from the command line begins afresh instead of resuming
+        let fresh_tab = match cli.command {
+            Some(Command::Stopwatch) => Some("stopwatch"),
+            Some(Command::Countdown { .. }) => Some("countdown"),
+            Some(Command::Pomodoro) => Some("pomodoro"),
+            Some(Command::Intervals) => Some("intervals"),
+            Some(Command::Ctl { .. } | Command::Status { .. }) | None => None,
+        };
+        app.session_path = Session::default_path();
+        app.restore_session(fresh_tab);
+
+        if let Some(tab) = &cli.tab {
+            app.tabs_group.select(tab)?;
+        }
+
+        match cli.command {
+            Some(Command::Stopwatch) => {
+                app.tabs_group.select("stopwatch")?;
+                app.handle_message(Message::ToggleStartPause)?;
+            }
+            Some(Command::Countdown { duration, until }) => {
+                app.tabs_group.select("countdown")?;
+                if let Some(duration) = duration {
+                    app.handle_message(Message::SetDuration(duration))?;
+                    app.handle_message(Message::ToggleStartPause)?;
+                } else if let Some(target) = until {
+                    app.handle_message(Message::SetTarget(target))?;
+                    app.handle_message(Message::ToggleStartPause)?;
+                }
+            }
+            Some(Command::Pomodoro) => {
+                app.tabs_group.select("pomodoro")?;
+                app.handle_message(Message::ToggleStartPause)?;
+            }
+            Some(Command::Intervals) => {
+                app.tabs_group.select("intervals")?;
+                app.handle_message(Message::ToggleStartPause)?;
+            }
+            Some(Command::Ctl { .. } | Command::Status { .. }) | None => (),
+        }
+
+        Ok(app)
+    }
+
+    /// An app with default settings whose tabs tell time by `clock`.
+    fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            active: true,
+            show_splash: true,
+            fps_counter: FpsCounter::new(clock.clone()),
+            tabs_group: TabGroup::new(clock.clone()),
+            config: Default::default(),
+            keymap: Default::default(),
+            config_watcher: None,
+            notice: None,
+            session_path: None,
+            history_path: None,
+            socket_path: None,
+            last_checkpoint: clock.now(),
+            next_tick: clock.now(),
+            clock,
+            dirty: true,
+            redraw_at: None,
+            drawn_view: Default::default(),
+            suspending: false,
+        }
+    }
+
     pub async fn run(&mut self) -> Result<()> {
         let mut tui = Tui::init()?;
-        let mut events = EventHandler::new(60.0);
-        
-        if true {
+
+        if self.show_splash {
             show_splash(&mut tui)?;
         }
 
+        // a second instance still works, it just cannot be scripted
+        let mut control = match self.socket_path.as_deref().map(ControlServer::bind) {
+            Some(Ok(server)) => Some(server),
+            Some(Err(err)) => {
+                self.show_notice(format!("{err:#}"));
+                None
+            }
+            None => None,
+        };
+        let mut signals = Signals::new()?;
+        let mut hooks = Hooks::default();
+
+        let event_loop = self.event_loop(&mut tui, &mut control, &mut signals, &mut hooks);
+        let result = match AssertUnwindSafe(event_loop).catch_unwind().await {
+            Ok(result) => result,
+            Err(panic) => {
+                // the panic hook restored the terminal, the timers are saved before the
+                // panic goes on to end the app
+                let _ = self.write_history();
+                let _ = self.save_session();
+                std::panic::resume_unwind(panic);
+            }
+        };
+
+        // also when the loop failed, e.g. because the terminal went away
+        self.write_history()?;
+        self.save_session()?;
+        hooks.wait().await;
+        result
+    }
+
+    async fn event_loop(
+        &mut self,
+        tui: &mut Tui,
+        control: &mut Option<ControlServer>,
+        signals: &mut Signals,
+        hooks: &mut Hooks,
+    ) -> Result<()> {
+        let mut events = EventHandler::default();
+
         while self.active {
-            self.draw(&mut tui)?;
-            let message = events.next().await?;
-            self.handle_message(message)?;
+            if self.dirty {
+                self.draw(tui)?;
+            }
+            self.next_tick = self.next_tick.min(self.clock.now() + self.tick_wait());
+            select! {
+                message = events.next() => self.handle_message(message?)?,
+                _ = tokio::time::sleep_until(self.next_tick.into()) => (),
+                Some(pending) = ControlServer::next(control) => {
+                    // a status bar asking every second leaves the screen alone
+                    self.dirty |= pending.request.action != "status";
+                    let response = self.handle_request(&pending.request);
+                    pending.respond(response);
+                }
+                signal = signals.next() => match signal {
+                    Signal::Quit => self.quit(),
+                    Signal::Suspend => self.handle_message(Message::Suspend)?,
+                },
+            }
+            self.tick_if_due()?;
+            if std::mem::take(&mut self.suspending) {
+                self.suspend(tui)?;
+            }
+            hooks.fire(&self.config.hooks, self.tabs_group.take_hook_events());
         }
         Ok(())
     }
 
+    /// Saves the timers, in case the app is never continued, and stops until it is.
+    fn suspend(&mut self, tui: &mut Tui) -> Result<()> {
+        if let Err(err) = self.save_session() {
+            self.show_notice(format!("{err:#}"));
+        }
+        tui.suspend()
+    }
+
+    fn restore_session(&mut self, skip: Option<&str>) {
+        let Some(path) = &self.session_path else {
+            return;
+        };
+
+        let notices = match Session::load(path) {
+            Ok(session) => self.tabs_group.restore_session(session, skip),
+            Err(err) => vec![format!("{err:#}")],
+        };
+        if !notices.is_empty() {
+            self.show_notice(notices.join(" | "));
+        }
+    }
+
+    fn save_session(&mut self) -> Result<()> {
+        self.last_checkpoint = self.clock.now();
+        match &self.session_path {
+            Some(path) => self.tabs_group.save_session().save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Appends the sessions tabs finished since the last call to the history file.
+    fn write_history(&mut self) -> Result<()> {
+        let records = self.tabs_group.take_records();
+        match &self.history_path {
+            Some(path) if !records.is_empty() => history::append(path, &records),
+            _ => Ok(()),
+        }
+    }
+
     fn handle_message(&mut self, message: Message) -> Result<()> {
+        // anything but a tick comes from the user and may change what is shown
+        if message != Message::Tick {
+            self.dirty = true;
+        }
+
         match message {
-            Message::ToggleStartPause | Message::Clear | Message::SetNumber(_) | Message::Edit
-                => self.tabs_group.current_tab().handle_message(message),
+            Message::Key(key) => {
+                let context = self.tabs_group.current_context();
+                let text_input = self.tabs_group.active_tab().text_input();
+                if let Some(message) = self.keymap.resolve(&context, key, text_input) {
+                    self.handle_message(message)?;
+                }
+            }
             Message::ChangeTab => self.next_tab(),
             Message::Tick => self.tick(),
+            Message::Resize => (),
+            Message::Suspend => self.suspending = true,
             Message::Quit => self.quit(),
+            _ => self.tabs_group.active_tab().handle_message(message),
         }
         Ok(())
     }
 
+    /// Answers a request from the control socket.
+    fn handle_request(&mut self, request: &Request) -> Response {
+        match self.apply_request(request) {
+            Ok(tabs) => Response::ok(tabs),
+            Err(err) => Response::error(format!("{err:#}")),
+        }
+    }
+
+    fn apply_request(&mut self, request: &Request) -> Result<Option<BTreeMap<String, TabStatus>>> {
+        let tab = request.tab.as_deref();
+        let message = match request.action.as_str() {
+            "status" => return Ok(Some(self.tabs_group.status(tab)?)),
+            "select_tab" => {
+                self.tabs_group.select(tab.context("select_tab needs a tab")?)?;
+                return Ok(None);
+            }
+            "set_duration" => {
+                let Some(duration) = &request.duration else {
+                    bail!("set_duration needs a duration");
+                };
+                Message::SetDuration(parse_duration(duration)?)
+            }
+            "set_target" => {
+                let Some(until) = &request.until else {
+                    bail!("set_target needs a time in until");
+                };
+                Message::SetTarget(parse_target(until, self.clock.utc(), self.clock.zone())?)
+            }
+            action => parse_action(action)?,
+        };
+
+        // changing tabs and quitting concern the whole app, not one tab
+        match tab {
+            Some(tab) if !matches!(message, Message::ChangeTab | Message::Quit) => {
+                self.tabs_group.tab_named(tab)?.handle_message(message);
+            }
+            _ => self.handle_message(message)?,
+        }
+        Ok(None)
+    }
+
     fn next_tab(&mut self) {
         self.tabs_group.increase_index();
     }
 
+    /// Ticks once `next_tick` has passed, whatever woke the event loop.
+    fn tick_if_due(&mut self) -> Result<()> {
+        if self.clock.now() >= self.next_tick {
+            self.handle_message(Message::Tick)?;
+        }
+        Ok(())
+    }
+
     fn tick(&mut self) {
-        self.fps_counter.tick()
+        // pushed back as far as it goes, the event loop brings it forward to when it is needed
+        self.next_tick = self.clock.now() + Self::MAX_TICK_INTERVAL;
+        self.tabs_group.tick_all();
+        let notices = self.tabs_group.take_notices();
+        if !notices.is_empty() {
+            self.show_notice(notices.join(" | "));
+        }
+        self.reload_config_if_changed();
+
+        if let Err(err) = self.write_history() {
+            self.show_notice(format!("{err:#}"));
+        }
+
+        let now = self.clock.now();
+        if now.saturating_duration_since(self.last_checkpoint) > Self::CHECKPOINT_INTERVAL {
+            if let Err(err) = self.save_session() {
+                self.show_notice(format!("{err:#}"));
+            }
+        }
+
+        if self.notice.as_ref().is_some_and(|(_, shown_at)| now.saturating_duration_since(*shown_at) > Self::NOTICE_DURATION) {
+            self.notice = None;
+            self.dirty = true;
+        }
+        if self.fps_counter.decay() {
+            self.dirty = true;
+        }
+
+        // the screen is redrawn when the tabs on it said they would change, when they changed
+        // in a way they could not say, and when they start or stop changing, e.g. when an
+        // alarm starts ringing or a countdown ends
+        let changed = self.tabs_group.take_changed();
+        let due = self.redraw_at.is_some_and(|at| now >= at);
+        if changed || due || self.view() != self.drawn_view {
+            self.dirty = true;
+        }
+    }
+
+    /// What is on screen: the keymap context, and whether it changes on its own.
+    fn view(&self) -> (String, bool) {
+        (self.tabs_group.current_context(), self.tabs_group.redraw_in().is_some())
+    }
+
+    /// How long to wait for input before the next tick: until the tabs on screen change, or
+    /// a tab in the background needs ticking, but no longer than `MAX_TICK_INTERVAL`.
+    fn tick_wait(&self) -> Duration {
+        let redraw = self.redraw_at.map(|at| at.saturating_duration_since(self.clock.now()));
+        let background = self.tabs_group.tick_in().map(|wait| wait.max(Self::BACKGROUND_TICK_INTERVAL));
+        sooner(redraw, background).map_or(Self::MAX_TICK_INTERVAL, |wait| wait.min(Self::MAX_TICK_INTERVAL))
+    }
+
+    fn show_notice(&mut self, notice: String) {
+        self.notice = Some((notice, self.clock.now()));
+        self.dirty = true;
+    }
+
+    fn apply_config(&mut self, config: Config) {
+        set_theme(config.theme);
+        set_time_format(config.time_format);
+        // the config was validated when loaded, so building the keymap cannot fail here
+        self.keymap = config.keymap().unwrap_or_default();
+        self.history_path = config.history_path();
+        self.tabs_group.apply_config(&config);
+        self.config = config;
+    }
+
+    fn reload_config_if_changed(&mut self) {
+        let Some(watcher) = self.config_watcher.as_mut() else {
+            return;
+        };
+        if !watcher.changed() {
+            return;
+        }
+
+        let notice = match Config::load(watcher.path()) {
+            Ok(config) => {
+                self.apply_config(config);
+                String::from("Config reloaded")
+            }
+            Err(err) => format!("{err:#}"),
+        };
+        self.show_notice(notice);
     }
 
     fn quit(&mut self) {
@@ -59,19 +438,39 @@ impl App {
     }
 
     fn draw(&mut self, tui: &mut Tui) -> Result<()> {
-        tui.draw(|frame| {
-            let layout = layout(frame.size());
-            frame.render_widget(self.title_paragraph(), layout[0]);
-            frame.render_widget(self.tabs_group.widget(), layout[1]);
-            frame.render_widget(self.fps_paragraph(), layout[2]);
-            let _ = self.tabs_group.current_tab().render_paragraph(frame, &layout[3]);
-            frame.render_widget(self.help_paragraph(), layout[4]);
-        })
+        tui.draw(|frame| self.render(frame))?;
+        self.drawn();
+        Ok(())
+    }
+
+    /// Notes that the screen is up to date, and when the tabs on it will change next. They
+    /// are redrawn no more often than the configured frame rate.
+    fn drawn(&mut self) {
+        let frame_interval = Duration::from_secs_f32(1.0 / self.config.fps);
+        let now = self.clock.now();
+        // frames are paced from when they were due, so time spent drawing and waking up late
+        // does not lower the frame rate
+        let frame_start = self.redraw_at.filter(|at| now.saturating_duration_since(*at) < frame_interval).unwrap_or(now);
+        self.fps_counter.tick();
+        self.dirty = false;
+        self.drawn_view = self.view();
+        self.redraw_at = self.tabs_group.redraw_in().map(|wait| (now + wait).max(frame_start + frame_interval));
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let layout = layout(frame.size());
+        frame.render_widget(self.title_paragraph(), layout[0]);
+        frame.render_widget(self.tabs_group.widget(), layout[1]);
+        frame.render_widget(self.fps_paragraph(), layout[2]);
+        let _ = self.tabs_group.current_tab().render_paragraph(frame, &layout[3]);
+        // a modal covers everything but the help bar, which lists its keys
+        let _ = self.tabs_group.render_modal(frame, &layout[0].union(layout[3]));
+        frame.render_widget(self.help_paragraph(), layout[4]);
     }
 
     fn title_paragraph(&mut self) -> Paragraph<'_> {
         let title = "timemann";
-        Paragraph::new(title).bold().bg(BG_COLOR)
+        Paragraph::new(title).bold().bg(theme().background)
     }
 
     fn fps_paragraph(&mut self) -> Paragraph<'_> {
@@ -79,26 +478,46 @@ impl App {
         Paragraph::new(fps)
             .dim()
             .right_aligned()
-            .bg(BG_COLOR)
+            .bg(theme().background)
     }
 
     fn help_paragraph(&mut self) -> Paragraph<'_> {
-        let mut start_vec = vec![
-            " Tab ".on_dark_gray().black().dim(),
-            " Next Tab ".dim(),
-        ];
-
-        let end_vec = vec![
-            " Q ".on_dark_gray().black().dim(),
-            " Quit ".dim(),
-        ];
-
-        // tab help text
-        let line1 = self.tabs_group.current_tab().help_text();
-        start_vec.extend(line1);
-        start_vec.extend(end_vec);
-
-        let help_text = Line::from(start_vec);
+        if let Some((notice, _)) = &self.notice {
+            return Paragraph::new(notice.as_str())
+                .yellow()
+                .centered();
+        }
+
+        let mut entries = vec![(Message::ChangeTab, "Next Tab")];
+        entries.extend(self.tabs_group.active_tab().help_entries());
+        entries.push((Message::Quit, "Quit"));
+
+        // the keys shown always come from the keymap, actions without a key are left out
+        let context = self.tabs_group.current_context();
+        let text_input = self.tabs_group.active_tab().text_input();
+        let mut keyed: Vec<(String, &str)> = vec![];
+        for (action, label) in entries {
+            let Some(key) = self.keymap.key_for(&context, action, text_input) else {
+                continue;
+            };
+            match keyed.last_mut() {
+                Some((keys, last_label)) if *last_label == label => {
+                    keys.push('/');
+                    keys.push_str(&key);
+                }
+                _ => keyed.push((key, label)),
+            }
+        }
+
+        let spans: Vec<Span> = keyed
+            .into_iter()
+            .flat_map(|(key, label)| [
+                format!(" {key} ").on_dark_gray().black().dim(),
+                format!(" {label} ").dim(),
+            ])
+            .collect();
+
+        let help_text = Line::from(spans);
         Paragraph::new(help_text)
             .gray()
             .centered()
@@ -107,10 +526,323 @@ impl App {
 
 impl Default for App {
     fn default() -> Self {
-        Self {
-            active: true,
-            fps_counter: Default::default(),
-            tabs_group: Default::default()
+        Self::with_clock(SharedClock::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+    use crate::clock::{secs, Clock, FakeClock};
+
+    const TABS: [&str; 9] = [
+        "stopwatch", "countdown", "timers", "alarms", "clocks", "pomodoro", "intervals", "history", "about",
+    ];
+
+    /// Widths around the switch to bigger digits at 150 columns, see `setup_big_timer_text`.
+    const WIDTHS: [u16; 3] = [80, 149, 150];
+
+    const HEIGHT: u16 = 24;
+
+    /// An app on a fake clock that renders into a buffer, driven like a user would.
+    struct Harness {
+        app: App,
+        clock: Arc<FakeClock>,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            Self::with_config(Config::default())
+        }
+
+        /// Files are looked up in a directory of the harness's own, as tests run in parallel
+        /// and some record sessions, and the fake clock shows local times in UTC, so
+        /// snapshots do not depend on the machine.
+        fn with_config(mut config: Config) -> Self {
+            static HARNESSES: AtomicUsize = AtomicUsize::new(0);
+            let dir = std::env::temp_dir()
+                .join("timemann-snapshots")
+                .join(format!("{}-{}", std::process::id(), HARNESSES.fetch_add(1, Ordering::Relaxed)));
+            let _ = std::fs::remove_dir_all(&dir);
+            config.history_file = Some(dir.join("history.jsonl"));
+            config.workouts_dir = Some(dir.join("workouts"));
+
+            let clock = FakeClock::new();
+            let mut app = App::with_clock(SharedClock::new(clock.clone()));
+            app.apply_config(config);
+            Self { app, clock }
+        }
+
+        fn select(&mut self, tab: &str) -> &mut Self {
+            self.app.tabs_group.select(tab).unwrap();
+            self
+        }
+
+        fn send(&mut self, message: Message) -> &mut Self {
+            self.app.handle_message(message).unwrap();
+            self
+        }
+
+        fn press(&mut self, code: KeyCode) -> &mut Self {
+            self.send(Message::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+        }
+
+        fn type_text(&mut self, text: &str) -> &mut Self {
+            for c in text.chars() {
+                self.press(KeyCode::Char(c));
+            }
+            self
+        }
+
+        /// Moves the clock forwards and lets the app notice, as the event loop would.
+        fn advance(&mut self, by: Duration) -> &mut Self {
+            self.clock.advance(by);
+            self.send(Message::Tick)
+        }
+
+        fn render(&mut self, width: u16) -> String {
+            // the frame rate depends on how the test moved the clock, not on what is shown
+            self.app.fps_counter.fps = 0.0;
+            let mut terminal = Terminal::new(TestBackend::new(width, HEIGHT)).unwrap();
+            terminal.draw(|frame| self.app.render(frame)).unwrap();
+            self.app.drawn();
+
+            let buffer = terminal.backend().buffer();
+            (0..buffer.area.height)
+                .map(|y| {
+                    let line: String = (0..buffer.area.width).map(|x| buffer.get(x, y).symbol()).collect();
+                    line.trim_end().to_string()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+
+    #[test]
+    fn every_tab_at_every_width() {
+        let mut harness = Harness::new();
+        for tab in TABS {
+            harness.select(tab);
+            for width in WIDTHS {
+                insta::assert_snapshot!(format!("{tab}_{width}"), harness.render(width));
+            }
+        }
+    }
+
+    #[test]
+    fn stopwatch_with_laps() {
+        let mut harness = Harness::new();
+        harness.select("stopwatch").press(KeyCode::Enter);
+        for lap in [62, 58, 65] {
+            harness.advance(secs(lap)).type_text("l");
+        }
+        harness.advance(Duration::from_millis(12_345));
+
+        for width in WIDTHS {
+            insta::assert_snapshot!(format!("stopwatch_laps_{width}"), harness.render(width));
+        }
+    }
+
+    #[test]
+    fn countdown_typing_a_duration() {
+        let mut harness = Harness::new();
+        harness.select("countdown").type_text("1h3");
+        insta::assert_snapshot!(harness.render(100));
+    }
+
+    #[test]
+    fn idle_countdown_keeps_bound_keys_until_something_is_typed() {
+        let mut harness = Harness::new();
+        harness.select("countdown").type_text("a");
+        assert_eq!(harness.app.tabs_group.current_context(), "timers");
+
+        harness.select("countdown").type_text("saturday 09:00").press(KeyCode::Enter);
+        assert!(harness.app.tabs_group.status(Some("countdown")).unwrap()["countdown"].state.is_running());
+    }
+
+    #[test]
+    fn countdown_running_and_paused() {
+        let mut harness = Harness::new();
+        harness.select("countdown").type_text("25m").press(KeyCode::Enter).advance(secs(61));
+        insta::assert_snapshot!("countdown_running", harness.render(150));
+
+        harness.press(KeyCode::Enter).advance(secs(5));
+        insta::assert_snapshot!("countdown_paused", harness.render(150));
+    }
+
+    #[test]
+    fn countdown_in_overtime() {
+        let mut config = Config::default();
+        config.countdown.overtime = true;
+        let mut harness = Harness::with_config(config);
+        harness.select("countdown").type_text("10s").press(KeyCode::Enter).advance(secs(10)).advance(secs(75));
+        insta::assert_snapshot!(harness.render(120));
+    }
+
+    #[test]
+    fn adjustment_flash() {
+        let mut harness = Harness::new();
+        harness.select("stopwatch").press(KeyCode::Enter).advance(secs(3)).type_text("+");
+        insta::assert_snapshot!(harness.render(100));
+    }
+
+    #[test]
+    fn timers_side_by_side() {
+        let mut harness = Harness::new();
+        harness
+            .select("timers")
+            .type_text("n")
+            .type_text("Tea")
+            .press(KeyCode::Enter)
+            .type_text("4m")
+            .press(KeyCode::Enter)
+            .type_text("n")
+            .press(KeyCode::Enter)
+            .type_text("90m")
+            .press(KeyCode::Enter)
+            .advance(secs(100));
+        insta::assert_snapshot!(harness.render(120));
+    }
+
+    #[test]
+    fn alarm_ringing_over_another_tab() {
+        let mut harness = Harness::new();
+        harness.select("alarms").type_text("n").type_text("09:01 Standup").press(KeyCode::Enter);
+        insta::assert_snapshot!("alarm_set", harness.render(100));
+
+        harness.select("stopwatch").advance(secs(60));
+        insta::assert_snapshot!("alarm_ringing", harness.render(100));
+    }
+
+    #[test]
+    fn snoozing_an_alarm_that_rings_while_typing_another() {
+        let mut harness = Harness::new();
+        harness.select("alarms").type_text("n").type_text("09:01 Standup").press(KeyCode::Enter);
+        harness.type_text("n").type_text("10:").advance(secs(60));
+        assert!(harness.app.tabs_group.status(Some("alarms")).unwrap()["alarms"].state.is_ringing());
+
+        harness.type_text("s");
+        assert!(harness.app.tabs_group.status(Some("alarms")).unwrap()["alarms"].state.is_armed());
+    }
+
+    #[test]
+    fn pomodoro_running() {
+        let mut harness = Harness::new();
+        harness.select("pomodoro").press(KeyCode::Enter).advance(secs(25 * 60)).press(KeyCode::Enter);
+        insta::assert_snapshot!(harness.render(100));
+    }
+
+    #[test]
+    fn intervals_running() {
+        let mut harness = Harness::new();
+        harness.select("intervals").press(KeyCode::Enter).advance(secs(5 * 60 + 25));
+        insta::assert_snapshot!(harness.render(100));
+    }
+
+    #[test]
+    fn notice_replaces_the_help_bar() {
+        let mut harness = Harness::new();
+        harness.app.notice = Some((String::from("Config reloaded"), harness.clock.now()));
+        insta::assert_snapshot!("notice", harness.render(80));
+
+        harness.advance(secs(6));
+        insta::assert_snapshot!("notice_expired", harness.render(80));
+    }
+
+    #[test]
+    fn tabs_in_configured_order_with_icons() {
+        let mut config = Config::default();
+        config.tabs.order = Some(vec![String::from("pomodoro"), String::from("countdown"), String::from("clocks")]);
+        config.tabs.icons = true;
+        let mut harness = Harness::with_config(config);
+        insta::assert_snapshot!(harness.render(100));
+    }
+
+    #[test]
+    fn redraws_a_running_stopwatch_every_frame_and_an_idle_one_never() {
+        let mut harness = Harness::new();
+        harness.render(80);
+        harness.advance(secs(5));
+        assert!(!harness.app.dirty);
+
+        harness.press(KeyCode::Enter);
+        assert!(harness.app.dirty);
+        harness.render(80);
+        harness.advance(Duration::from_millis(5));
+        assert!(!harness.app.dirty, "redrawn faster than the frame rate");
+        harness.advance(Duration::from_millis(15));
+        assert!(harness.app.dirty);
+
+        harness.press(KeyCode::Enter).render(80);
+        harness.advance(secs(5));
+        assert!(!harness.app.dirty);
+    }
+
+    #[test]
+    fn redraws_the_clocks_once_a_second() {
+        let mut harness = Harness::new();
+        harness.select("clocks").render(80);
+        harness.advance(Duration::from_millis(990));
+        assert!(!harness.app.dirty);
+        harness.advance(Duration::from_millis(10));
+        assert!(harness.app.dirty);
+    }
+
+    #[test]
+    fn frame_rate_drops_to_zero_once_nothing_is_drawn() {
+        let mut harness = Harness::new();
+        harness.render(80);
+        harness.app.fps_counter.fps = 30.0;
+        harness.advance(Duration::from_millis(500));
+        assert_eq!(harness.app.fps_counter.fps, 30.0);
+        assert!(!harness.app.dirty);
+
+        harness.advance(Duration::from_millis(500));
+        assert_eq!(harness.app.fps_counter.fps, 0.0);
+        assert!(harness.app.dirty);
+    }
+
+    #[test]
+    fn steady_input_does_not_hold_off_ticks() {
+        let mut harness = Harness::new();
+        harness.select("alarms").type_text("n").type_text("09:01 Standup").press(KeyCode::Enter);
+        harness.select("about").advance(secs(1));
+
+        // keys every half second, as the event loop would see them without a tick
+        for _ in 0..120 {
+            harness.clock.advance(Duration::from_millis(500));
+            harness.press(KeyCode::Char('x'));
+            harness.app.tick_if_due().unwrap();
         }
+        assert!(harness.app.tabs_group.status(Some("alarms")).unwrap()["alarms"].state.is_ringing());
+    }
+
+    #[test]
+    fn reports_an_alarm_missed_during_a_suspend() {
+        let mut harness = Harness::new();
+        harness.select("alarms").type_text("n").type_text("09:01 Standup").press(KeyCode::Enter);
+        harness.advance(secs(30 * 60));
+        assert!(!harness.app.tabs_group.status(Some("alarms")).unwrap()["alarms"].state.is_ringing());
+        let (notice, _) = harness.app.notice.as_ref().unwrap();
+        assert_eq!(notice, "Missed alarm 'Standup' at Sat 09:01");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn redraws_when_an_alarm_rings_over_an_idle_tab() {
+        let mut harness = Harness::new();
+        harness.select("alarms").type_text("n").type_text("09:01 Standup").press(KeyCode::Enter);
+        harness.select("about").render(80);
+        harness.advance(secs(30));
+        assert!(!harness.app.dirty);
+
+        harness.advance(secs(30));
+        assert!(harness.app.dirty);
+    }
+}
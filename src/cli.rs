@@ -0,0 +1,157 @@
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+
+use crate::clock::LocalZone;
+use crate::control;
+use crate::duration::parse_duration;
+use crate::status::StatusFormat;
+use crate::target::{parse_target, Target};
+
+
+/// A simple stopwatch and countdown timer for the terminal.
+#[derive(Debug, Clone, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Tab to show on startup (stopwatch, countdown, timers, alarms, clocks, pomodoro, intervals, history, about)
+    #[arg(long, value_name = "NAME")]
+    pub tab: Option<String>,
+
+    /// Skip the splash screen
+    #[arg(long)]
+    pub no_splash: bool,
+
+    /// Config file to use instead of `$XDG_CONFIG_HOME/timemann/config.toml`
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Print the time on one self-updating line instead of opening the app
+    #[arg(long, global = true, conflicts_with = "json_lines")]
+    pub plain: bool,
+
+    /// Print one JSON object per second instead of opening the app
+    #[arg(long, global = true)]
+    pub json_lines: bool,
+
+    /// Control socket to listen on, or to send to with `ctl`,
+    /// instead of `$XDG_RUNTIME_DIR/timemann.sock`
+    #[arg(long, global = true, value_name = "PATH")]
+    pub socket: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// How progress is printed when running without the interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Plain,
+    JsonLines,
+}
+
+impl Cli {
+    pub fn socket_path(&self) -> PathBuf {
+        self.socket.clone().unwrap_or_else(control::default_path)
+    }
+
+    /// The headless output asked for, plain text when a timer is started while stdout is
+    /// not a terminal, or `None` to open the app.
+    pub fn output(&self) -> Option<Output> {
+        if self.json_lines {
+            Some(Output::JsonLines)
+        } else if self.plain || (self.starts_timer() && !io::stdout().is_terminal()) {
+            Some(Output::Plain)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the command starts a timer that can run without the interface.
+    fn starts_timer(&self) -> bool {
+        matches!(
+            self.command,
+            Some(Command::Countdown { duration: Some(_), .. } | Command::Countdown { until: Some(_), .. } | Command::Stopwatch)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Open the stopwatch tab and start it right away
+    Stopwatch,
+
+    /// Open the countdown tab, optionally starting it with the given duration
+    Countdown {
+        /// Duration such as `25m`, `1h30m` or `90s`
+        #[arg(value_parser = parse_cli_duration)]
+        duration: Option<Duration>,
+
+        /// Count down to a time or date instead, e.g. `17:30`, `next monday 09:00`
+        /// or `2026-12-31 23:59:59 Europe/Berlin`
+        #[arg(long, value_name = "TIME", conflicts_with = "duration", value_parser = parse_cli_target)]
+        until: Option<Target>,
+    },
+
+    /// Open the pomodoro tab and start the first work session
+    Pomodoro,
+
+    /// Open the intervals tab and start the first workout
+    Intervals,
+
+    /// Send an action to the running app, e.g. `ctl toggle_start_pause`,
+    /// `ctl set_duration 25m --tab countdown` or `ctl status`
+    Ctl {
+        /// An action from the keymap, or status, select_tab, set_duration or set_target
+        action: String,
+
+        /// The duration for set_duration, the time for set_target or the tab for select_tab
+        value: Option<String>,
+
+        /// Tab the action applies to, the one in front by default
+        #[arg(long, value_name = "NAME")]
+        tab: Option<String>,
+    },
+
+    /// Print the running timer of the app for a status bar, e.g. waybar, i3bar or tmux
+    Status {
+        /// How to print it
+        #[arg(long, value_enum, default_value_t = StatusFormat::Plain)]
+        format: StatusFormat,
+
+        /// Text to print, with {label}, {state}, {tab}, {elapsed}, {remaining} and {time},
+        /// which take a format such as `{remaining:%M:%S}`
+        #[arg(long, default_value = "{label} {time}")]
+        template: String,
+
+        /// Tab to show, by default the one with the most pressing timer
+        #[arg(long, value_name = "NAME")]
+        tab: Option<String>,
+
+        /// Keep printing a line every interval instead of exiting
+        #[arg(long)]
+        watch: bool,
+
+        /// Time between lines with --watch
+        #[arg(long, default_value = "1s", value_parser = parse_cli_interval)]
+        interval: Duration,
+    },
+}
+
+fn parse_cli_duration(input: &str) -> Result<Duration, String> {
+    parse_duration(input).map_err(|err| err.to_string())
+}
+
+fn parse_cli_interval(input: &str) -> Result<Duration, String> {
+    let interval = parse_cli_duration(input)?;
+    if interval.is_zero() {
+        return Err(String::from("the interval must be longer than zero"));
+    }
+    Ok(interval)
+}
+
+fn parse_cli_target(input: &str) -> Result<Target, String> {
+    parse_target(input, Utc::now(), LocalZone::System).map_err(|err| err.to_string())
+}
@@ -0,0 +1,204 @@
+use std::fmt::{self, Debug};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::{DateTime, FixedOffset, Local, MappedLocalTime, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::{Tz, TzOffset};
+
+
+/// Where timers and tabs get the current time from, so tests can move time by hand
+/// instead of sleeping.
+pub trait Clock: Send + Sync {
+    /// Monotonic time, for measuring how long a timer ran.
+    fn now(&self) -> Instant;
+
+    /// Wall-clock time, for targets, alarms and the time zones.
+    fn utc(&self) -> DateTime<Utc>;
+
+    /// The zone local times are shown and typed in.
+    fn zone(&self) -> LocalZone {
+        LocalZone::System
+    }
+}
+
+/// The zone of the local time: the system's, or a fixed one so tests do not depend on
+/// the machine they run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalZone {
+    System,
+    Fixed(Tz),
+}
+
+/// The offset from UTC of a `LocalZone` at some point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalOffset {
+    System(FixedOffset),
+    Fixed(TzOffset),
+}
+
+impl Offset for LocalOffset {
+    fn fix(&self) -> FixedOffset {
+        match self {
+            LocalOffset::System(offset) => *offset,
+            LocalOffset::Fixed(offset) => offset.fix(),
+        }
+    }
+}
+
+impl fmt::Display for LocalOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocalOffset::System(offset) => write!(f, "{offset}"),
+            LocalOffset::Fixed(offset) => write!(f, "{offset}"),
+        }
+    }
+}
+
+impl TimeZone for LocalZone {
+    type Offset = LocalOffset;
+
+    fn from_offset(offset: &LocalOffset) -> Self {
+        match offset {
+            LocalOffset::System(_) => LocalZone::System,
+            LocalOffset::Fixed(offset) => LocalZone::Fixed(Tz::from_offset(offset)),
+        }
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<LocalOffset> {
+        match self {
+            LocalZone::System => Local.offset_from_local_date(local).map(LocalOffset::System),
+            LocalZone::Fixed(zone) => zone.offset_from_local_date(local).map(LocalOffset::Fixed),
+        }
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<LocalOffset> {
+        match self {
+            LocalZone::System => Local.offset_from_local_datetime(local).map(LocalOffset::System),
+            LocalZone::Fixed(zone) => zone.offset_from_local_datetime(local).map(LocalOffset::Fixed),
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> LocalOffset {
+        match self {
+            LocalZone::System => LocalOffset::System(Local.offset_from_utc_date(utc)),
+            LocalZone::Fixed(zone) => LocalOffset::Fixed(zone.offset_from_utc_date(utc)),
+        }
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> LocalOffset {
+        match self {
+            LocalZone::System => LocalOffset::System(Local.offset_from_utc_datetime(utc)),
+            LocalZone::Fixed(zone) => LocalOffset::Fixed(zone.offset_from_utc_datetime(utc)),
+        }
+    }
+}
+
+/// The real time of the system.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn utc(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock shared by everything that tells time, the system clock by default.
+#[derive(Clone)]
+pub struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self(clock)
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock))
+    }
+}
+
+impl Deref for SharedClock {
+    type Target = dyn Clock;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl Debug for SharedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SharedClock")
+    }
+}
+
+/// Two handles are equal when they share the same clock.
+impl PartialEq for SharedClock {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// A clock that only moves when told to, and shows local times in UTC.
+#[cfg(test)]
+#[derive(Debug)]
+pub struct FakeClock {
+    start: Instant,
+    start_utc: DateTime<Utc>,
+    offset: std::sync::Mutex<std::time::Duration>,
+}
+
+#[cfg(test)]
+impl FakeClock {
+    /// A clock standing at 09:00 UTC on 2026-03-14, where tests start unless they need
+    /// another time.
+    pub fn new() -> Arc<Self> {
+        use chrono::TimeZone;
+
+        Self::at(Utc.with_ymd_and_hms(2026, 3, 14, 9, 0, 0).unwrap())
+    }
+
+    /// A clock standing at `utc`.
+    pub fn at(utc: DateTime<Utc>) -> Arc<Self> {
+        Arc::new(Self {
+            start: Instant::now(),
+            start_utc: utc,
+            offset: Default::default(),
+        })
+    }
+
+    pub fn advance(&self, by: std::time::Duration) {
+        *self.offset.lock().unwrap() += by;
+    }
+
+    fn offset(&self) -> std::time::Duration {
+        *self.offset.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.start + self.offset()
+    }
+
+    fn utc(&self) -> DateTime<Utc> {
+        self.start_utc + self.offset()
+    }
+
+    fn zone(&self) -> LocalZone {
+        LocalZone::Fixed(Tz::UTC)
+    }
+}
+
+/// Whole seconds, to keep tests that move the clock around short.
+#[cfg(test)]
+pub fn secs(secs: u64) -> std::time::Duration {
+    std::time::Duration::from_secs(secs)
+}
@@ -1,5 +1,51 @@
+use std::sync::RwLock;
+
 use ratatui::style::Color;
+use serde::Deserialize;
 
 
 pub const BG_COLOR: Color = Color::Rgb(16, 23, 48);
 pub const BG_MAIN_COLOR: Color = Color::Rgb(17, 32, 63);
+
+static THEME: RwLock<Theme> = RwLock::new(Theme::DEFAULT);
+
+/// Colors used across the app, configurable through the `[theme]` config section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Background of the top bar
+    pub background: Color,
+    /// Background of the main timer area
+    pub main_background: Color,
+    /// Timer text while running
+    pub running: Color,
+    /// Timer text while paused or stopped
+    pub stopped: Color,
+    /// Countdown text once it runs past zero
+    pub overtime: Color,
+}
+
+impl Theme {
+    pub const DEFAULT: Theme = Theme {
+        background: BG_COLOR,
+        main_background: BG_MAIN_COLOR,
+        running: Color::Green,
+        stopped: Color::Red,
+        overtime: Color::Magenta,
+    };
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Returns the theme currently in use.
+pub fn theme() -> Theme {
+    *THEME.read().unwrap_or_else(|err| err.into_inner())
+}
+
+pub fn set_theme(theme: Theme) {
+    *THEME.write().unwrap_or_else(|err| err.into_inner()) = theme;
+}
@@ -0,0 +1,343 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use chrono_tz::Tz;
+use notify_rust::Notification;
+use serde::Deserialize;
+
+use crate::colors::Theme;
+use crate::duration::deserialize_duration;
+use crate::history;
+use crate::hooks::{self, HookKind};
+use crate::keymap::{Keymap, KeysConfig};
+use crate::tabs;
+use crate::ui::TimeFormat;
+use crate::workout;
+
+
+/// Settings read from `$XDG_CONFIG_HOME/timemann/config.toml`.
+/// Every field is optional, missing ones fall back to their defaults.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub theme: Theme,
+    /// How many times per second the screen is refreshed
+    pub fps: f32,
+    /// Tab shown on startup
+    pub default_tab: Option<String>,
+    /// Whether to show the splash screen on startup
+    pub splash: bool,
+    pub notification: NotificationConfig,
+    pub time_format: TimeFormat,
+    pub countdown: CountdownConfig,
+    pub pomodoro: PomodoroConfig,
+    pub alarms: AlarmsConfig,
+    pub world_clock: WorldClockConfig,
+    pub hooks: HooksConfig,
+    pub tabs: TabsConfig,
+    /// Key bindings per context, see `keymap`
+    pub keys: KeysConfig,
+    /// Where finished timers are recorded, `$XDG_DATA_HOME/timemann/history.jsonl` by default
+    pub history_file: Option<PathBuf>,
+    /// Where the intervals tab finds workouts, `$XDG_CONFIG_HOME/timemann/workouts` by default
+    pub workouts_dir: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            fps: 60.0,
+            default_tab: None,
+            splash: true,
+            notification: NotificationConfig::default(),
+            time_format: TimeFormat::default(),
+            countdown: CountdownConfig::default(),
+            pomodoro: PomodoroConfig::default(),
+            alarms: AlarmsConfig::default(),
+            world_clock: WorldClockConfig::default(),
+            hooks: HooksConfig::default(),
+            tabs: TabsConfig::default(),
+            keys: KeysConfig::default(),
+            history_file: None,
+            workouts_dir: None,
+        }
+    }
+}
+
+/// Text of the desktop notifications sent when a timer finishes or an alarm rings.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationConfig {
+    pub summary: String,
+    pub body: String,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            summary: String::from("TimeMann Alert"),
+            body: String::from("Countdown finished!"),
+        }
+    }
+}
+
+impl NotificationConfig {
+    /// Sends a desktop notification with `body` under the configured summary.
+    pub fn show(&self, body: &str) {
+        // tests drive timers to zero and ring alarms, without popping up notifications on the desktop
+        if cfg!(test) {
+            return;
+        }
+
+        let _ = Notification::new().summary(&self.summary).body(body).show();
+    }
+}
+
+/// Settings shared by the countdown tab and every timer.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CountdownConfig {
+    /// Keep counting past zero as overtime until dismissed, instead of clearing
+    pub overtime: bool,
+}
+
+/// Phase lengths of the pomodoro tab.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PomodoroConfig {
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub work: Duration,
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub short_break: Duration,
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub long_break: Duration,
+    /// Work sessions before a long break
+    pub cycles: u32,
+    /// Start the next phase right away instead of waiting for confirmation
+    pub auto_start: bool,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work: Duration::from_secs(25 * 60),
+            short_break: Duration::from_secs(5 * 60),
+            long_break: Duration::from_secs(15 * 60),
+            cycles: 4,
+            auto_start: false,
+        }
+    }
+}
+
+/// Which tabs are shown, and in what order.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TabsConfig {
+    /// Tabs to show, in this order. All of them, in the default order, when unset
+    pub order: Option<Vec<String>>,
+    /// Tabs to leave out
+    pub disabled: Vec<String>,
+    /// Show an icon before each title
+    pub icons: bool,
+}
+
+/// Settings of the alarms tab.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlarmsConfig {
+    /// How long a snoozed alarm waits before ringing again
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub snooze: Duration,
+}
+
+impl Default for AlarmsConfig {
+    fn default() -> Self {
+        Self {
+            snooze: Duration::from_secs(9 * 60),
+        }
+    }
+}
+
+/// How the world clock tab shows its zones.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockView {
+    /// One row per zone
+    #[default]
+    Table,
+    /// The time of each zone in big digits
+    Big,
+}
+
+/// A time zone shown on the world clock tab.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ZoneConfig {
+    /// Shown instead of the city from the zone name, e.g. `Office`
+    pub name: Option<String>,
+    pub zone: Tz,
+}
+
+impl ZoneConfig {
+    /// The configured name, or the city of the zone, e.g. `New York` for `America/New_York`.
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => {
+                let zone = self.zone.name();
+                zone.rsplit('/').next().unwrap_or(zone).replace('_', " ")
+            }
+        }
+    }
+}
+
+/// Settings of the world clock tab.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorldClockConfig {
+    pub view: ClockView,
+    pub zones: Vec<ZoneConfig>,
+}
+
+impl Default for WorldClockConfig {
+    fn default() -> Self {
+        let zones = [Tz::UTC, Tz::America__New_York, Tz::Europe__London, Tz::Asia__Tokyo]
+            .into_iter()
+            .map(|zone| ZoneConfig { name: None, zone })
+            .collect();
+
+        Self {
+            view: ClockView::default(),
+            zones,
+        }
+    }
+}
+
+/// Shell commands run when timers change state. They get `TIMEMANN_*` environment
+/// variables describing the timer, see `HookEvent`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    pub on_start: Option<String>,
+    pub on_pause: Option<String>,
+    pub on_finish: Option<String>,
+    pub on_lap: Option<String>,
+    pub on_phase_change: Option<String>,
+    /// How long a hook may run before it is killed
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub timeout: Duration,
+    /// Where hook results and output are logged, `$XDG_STATE_HOME/timemann/hooks.log` by default
+    pub log_file: Option<PathBuf>,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_start: None,
+            on_pause: None,
+            on_finish: None,
+            on_lap: None,
+            on_phase_change: None,
+            timeout: Duration::from_secs(10),
+            log_file: None,
+        }
+    }
+}
+
+impl HooksConfig {
+    /// The command configured for `kind`, if any.
+    pub fn command(&self, kind: HookKind) -> Option<&str> {
+        let command = match kind {
+            HookKind::Start => &self.on_start,
+            HookKind::Pause => &self.on_pause,
+            HookKind::Finish => &self.on_finish,
+            HookKind::Lap => &self.on_lap,
+            HookKind::PhaseChange => &self.on_phase_change,
+        };
+        command.as_deref().filter(|command| !command.trim().is_empty())
+    }
+
+    /// The hook log to use, the configured one or the default location.
+    pub fn log_path(&self) -> Option<PathBuf> {
+        self.log_file.clone().or_else(hooks::default_log_path)
+    }
+}
+
+impl Config {
+    /// Default location of the config file, if a config directory can be found.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("timemann").join("config.toml"))
+    }
+
+    /// The history file to use, the configured one or the default location.
+    pub fn history_path(&self) -> Option<PathBuf> {
+        self.history_file.clone().or_else(history::default_path)
+    }
+
+    /// The workouts directory to use, the configured one or the default location.
+    pub fn workouts_path(&self) -> Option<PathBuf> {
+        self.workouts_dir.clone().or_else(workout::workouts_dir)
+    }
+
+    /// Reads the config at `path`, using defaults when the file does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read config file {}", path.display()))
+            }
+        };
+
+        Self::parse(&text).with_context(|| format!("invalid config file {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let config: Config = toml::from_str(text).map_err(|err| describe_toml_error(text, err))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(1.0..=240.0).contains(&self.fps) {
+            bail!("fps must be between 1 and 240, got {}", self.fps);
+        }
+        if self.pomodoro.cycles == 0 {
+            bail!("pomodoro.cycles must be at least 1");
+        }
+        let pomodoro = &self.pomodoro;
+        if [pomodoro.work, pomodoro.short_break, pomodoro.long_break].iter().any(Duration::is_zero) {
+            bail!("pomodoro phases must be longer than zero");
+        }
+        if self.alarms.snooze.is_zero() {
+            bail!("alarms.snooze must be longer than zero");
+        }
+        if self.hooks.timeout.is_zero() {
+            bail!("hooks.timeout must be longer than zero");
+        }
+        tabs::enabled_tab_ids(&self.tabs)?;
+        self.keymap()?;
+        Ok(())
+    }
+
+    pub fn keymap(&self) -> Result<Keymap> {
+        Keymap::new(&self.keys)
+    }
+}
+
+/// Turns a TOML error into a single line such as `line 3, column 7: unknown field ...`.
+pub fn describe_toml_error(text: &str, err: toml::de::Error) -> anyhow::Error {
+    let message = err.message().trim().to_string();
+    match err.span() {
+        Some(span) => {
+            let before = text.get(..span.start).unwrap_or(text);
+            let line = before.matches('\n').count() + 1;
+            let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+            anyhow::anyhow!("line {line}, column {column}: {message}")
+        }
+        None => anyhow::anyhow!(message),
+    }
+}
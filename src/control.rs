@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::tabs::TabStatus;
+
+
+/// One line sent to the control socket, e.g. `{"action": "toggle_start_pause", "tab": "countdown"}`.
+///
+/// `action` is any action name from the keymap, or one of `status`, `select_tab`,
+/// `set_duration` and `set_target`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Request {
+    pub action: String,
+    /// Tab the action applies to, the one in front when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab: Option<String>,
+    /// For `set_duration`, e.g. `25m`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>,
+    /// For `set_target`, e.g. `17:30` or `next monday 09:00`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+}
+
+/// The line sent back for every request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// For `status`, keyed by tab name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tabs: Option<BTreeMap<String, TabStatus>>,
+}
+
+impl Request {
+    /// Builds the request for `timemann ctl <action> [value] [--tab NAME]`. The value is
+    /// the duration, the target time or the tab to select, depending on the action.
+    pub fn from_args(action: String, value: Option<String>, tab: Option<String>) -> Result<Self> {
+        let mut request = Self {
+            action,
+            tab,
+            duration: None,
+            until: None,
+        };
+
+        let needs_value = matches!(request.action.as_str(), "set_duration" | "set_target" | "select_tab");
+        match (request.action.as_str(), value) {
+            ("set_duration", Some(value)) => request.duration = Some(value),
+            ("set_target", Some(value)) => request.until = Some(value),
+            ("select_tab", Some(value)) => request.tab = Some(value),
+            (action, None) if needs_value => bail!("{action} needs a value"),
+            (action, Some(value)) if !needs_value => bail!("{action} takes no value, got '{value}'"),
+            _ => (),
+        }
+        Ok(request)
+    }
+}
+
+impl Response {
+    pub fn ok(tabs: Option<BTreeMap<String, TabStatus>>) -> Self {
+        Self { ok: true, error: None, tabs }
+    }
+
+    pub fn error(error: impl Into<String>) -> Self {
+        Self { ok: false, error: Some(error.into()), tabs: None }
+    }
+}
+
+/// A request waiting for the app to answer it.
+pub struct Pending {
+    pub request: Request,
+    reply: oneshot::Sender<Response>,
+}
+
+impl Pending {
+    pub fn respond(self, response: Response) {
+        // the client may have hung up already, there is nobody to tell then
+        let _ = self.reply.send(response);
+    }
+}
+
+/// Default location of the socket, `$XDG_RUNTIME_DIR/timemann.sock` or a per-user file in
+/// the temporary directory.
+pub fn default_path() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join("timemann.sock"),
+        None => {
+            let user = std::env::var("USER").unwrap_or_else(|_| String::from("user"));
+            std::env::temp_dir().join(format!("timemann-{user}.sock"))
+        }
+    }
+}
+
+/// Listens on the control socket and hands requests to the app. The socket file is
+/// removed when the server is dropped.
+pub struct ControlServer {
+    path: PathBuf,
+    requests: mpsc::Receiver<Pending>,
+}
+
+impl ControlServer {
+    /// Waits for the next request. Never completes when there is no server.
+    pub async fn next(server: &mut Option<Self>) -> Option<Pending> {
+        match server {
+            Some(server) => server.requests.recv().await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    use anyhow::{bail, Context, Result};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+    use tokio::sync::{mpsc, oneshot};
+
+    use super::{ControlServer, Pending, Request, Response};
+
+    impl ControlServer {
+        /// Starts listening at `path`. Fails when another instance is already listening there,
+        /// a socket file left behind by one that crashed is replaced.
+        pub fn bind(path: &Path) -> Result<Self> {
+            if path.exists() {
+                if std::os::unix::net::UnixStream::connect(path).is_ok() {
+                    bail!("another timemann is already listening on {}", path.display());
+                }
+                fs::remove_file(path).with_context(|| format!("failed to remove stale socket {}", path.display()))?;
+            }
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+            }
+
+            let listener = UnixListener::bind(path).with_context(|| format!("failed to listen on {}", path.display()))?;
+            // only the user running timemann may control it
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+
+            let (sender, requests) = mpsc::channel(16);
+            tokio::spawn(accept(listener, sender));
+
+            Ok(Self {
+                path: path.to_path_buf(),
+                requests,
+            })
+        }
+    }
+
+    async fn accept(listener: UnixListener, sender: mpsc::Sender<Pending>) {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve(stream, sender.clone()));
+        }
+    }
+
+    /// Answers each line of a connection until the client closes it.
+    async fn serve(stream: UnixStream, sender: mpsc::Sender<Pending>) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str::<Request>(&line) {
+                Ok(request) => {
+                    let (reply, answer) = oneshot::channel();
+                    if sender.send(Pending { request, reply }).await.is_err() {
+                        break;
+                    }
+                    answer.await.unwrap_or_else(|_| Response::error("timemann is shutting down"))
+                }
+                Err(err) => Response::error(format!("invalid request: {err}")),
+            };
+
+            let mut text = serde_json::to_string(&response)?;
+            text.push('\n');
+            writer.write_all(text.as_bytes()).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends one request to the instance listening at `path` and waits for its answer.
+    pub async fn send(path: &Path, request: &Request) -> Result<Response> {
+        let stream = UnixStream::connect(path)
+            .await
+            .with_context(|| format!("no timemann is listening on {}", path.display()))?;
+        let (reader, mut writer) = stream.into_split();
+
+        let mut text = serde_json::to_string(request)?;
+        text.push('\n');
+        writer.write_all(text.as_bytes()).await?;
+
+        let line = BufReader::new(reader)
+            .lines()
+            .next_line()
+            .await?
+            .context("timemann closed the connection without answering")?;
+        serde_json::from_str(&line).context("invalid response from timemann")
+    }
+}
+
+#[cfg(unix)]
+pub use unix::send;
+
+#[cfg(not(unix))]
+impl ControlServer {
+    pub fn bind(_path: &Path) -> Result<Self> {
+        anyhow::bail!("the control socket needs a Unix domain socket, which this platform lacks");
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn send(_path: &Path, _request: &Request) -> Result<Response> {
+    anyhow::bail!("the control socket needs a Unix domain socket, which this platform lacks");
+}
+
+/// Runs `timemann ctl`: sends one request, prints the response as a line of JSON and
+/// fails when the app reports an error.
+pub async fn run_ctl(path: &Path, request: Request) -> Result<ExitCode> {
+    let response = send(path, &request).await?;
+    println!("{}", serde_json::to_string(&response)?);
+
+    if !response.ok {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(action: &str, value: Option<&str>, tab: Option<&str>) -> Result<Request> {
+        Request::from_args(action.to_string(), value.map(String::from), tab.map(String::from))
+    }
+
+    #[test]
+    fn values_go_where_the_action_needs_them() {
+        for (action, value, tab, expected) in [
+            ("toggle_start_pause", None, None, r#"{"action":"toggle_start_pause"}"#),
+            ("clear", None, Some("countdown"), r#"{"action":"clear","tab":"countdown"}"#),
+            ("set_duration", Some("25m"), None, r#"{"action":"set_duration","duration":"25m"}"#),
+            ("set_target", Some("17:30"), Some("timers"), r#"{"action":"set_target","tab":"timers","until":"17:30"}"#),
+            ("select_tab", Some("pomodoro"), None, r#"{"action":"select_tab","tab":"pomodoro"}"#),
+        ] {
+            let request = request(action, value, tab).unwrap();
+            let line = serde_json::to_string(&request).unwrap();
+            assert_eq!(line, expected, "{action}");
+            assert_eq!(serde_json::from_str::<Request>(&line).unwrap(), request, "{action}");
+        }
+    }
+
+    #[test]
+    fn checks_which_actions_take_a_value() {
+        for (action, value, expected) in [
+            ("set_duration", None, "set_duration needs a value"),
+            ("set_target", None, "set_target needs a value"),
+            ("select_tab", None, "select_tab needs a value"),
+            ("status", Some("now"), "status takes no value, got 'now'"),
+            ("toggle_start_pause", Some("25m"), "toggle_start_pause takes no value, got '25m'"),
+        ] {
+            assert_eq!(request(action, value, None).unwrap_err().to_string(), expected, "{action}");
+        }
+    }
+
+    #[test]
+    fn parses_requests_and_responses() {
+        let request: Request = serde_json::from_str(r#"{"action": "status", "tab": "alarms"}"#).unwrap();
+        assert_eq!(request, Request { action: String::from("status"), tab: Some(String::from("alarms")), duration: None, until: None });
+
+        let err = serde_json::from_str::<Request>(r#"{"action": "status", "tabs": "alarms"}"#).unwrap_err();
+        assert!(err.to_string().starts_with("unknown field `tabs`"), "{err}");
+
+        assert_eq!(serde_json::to_string(&Response::ok(None)).unwrap(), r#"{"ok":true}"#);
+        assert_eq!(
+            serde_json::to_string(&Response::error("unknown tab 'lap'")).unwrap(),
+            r#"{"ok":false,"error":"unknown tab 'lap'"}"#
+        );
+    }
+}
@@ -1,217 +1,919 @@
-use std::ops::Add;
 use std::time::Duration;
 
-use anyhow::{Ok, Result};
-use notify_rust::Notification;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tui_big_text::BigText;
 use ratatui::prelude::*;
-
-use crate::tabs::AppTab;
-use crate::{msg::Message, states::AppState, timer::Timer};
+use ratatui::widgets::Paragraph;
+
+use crate::clock::{LocalZone, SharedClock};
+use crate::colors::theme;
+use crate::config::{Config, NotificationConfig};
+use crate::duration::parse_duration;
+use crate::history::{Outcome, SessionRecord};
+use crate::hooks::{HookEvent, HookKind};
+use crate::keymap::TextInput;
+use crate::target::{is_target, parse_target, Target};
+use crate::tabs::{AppTab, TabStatus, TimerState};
+use crate::{msg::Message, states::AppState, timer::{Timer, TimerSnapshot}};
 use crate::ui::*;
 
 
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedCountdown {
+    state: AppState,
+    timer: TimerSnapshot,
+    init_duration: Duration,
+    input: String,
+    #[serde(default)]
+    started_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    target: Option<Target>,
+    #[serde(default)]
+    overtime: bool,
+}
+
+/// What was typed in edit mode, a length or a point in time to count down to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CountdownInput {
+    Duration(Duration),
+    Target(Target),
+}
+
+impl CountdownInput {
+    fn duration(&self, now: DateTime<Utc>) -> Duration {
+        match self {
+            CountdownInput::Duration(duration) => *duration,
+            CountdownInput::Target(target) => target.remaining(now).unwrap_or_default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct CountdownTab {
+    /// Name shown in the timers list and in notifications, may be empty
+    label: String,
     state: AppState,
     timer: Timer,
     init_duration: Duration,
-    index_counter: u8,
-    can_start: bool
+    /// Wall-clock time the countdown ends at, when it was set as one
+    target: Option<Target>,
+    input: String,
+    cursor: usize,
+    can_start: bool,
+    notification: NotificationConfig,
+    /// Keep counting past zero instead of clearing, from the config
+    allow_overtime: bool,
+    /// Whether the countdown ran out and now counts overtime
+    overtime: bool,
+    /// Wall time of the first start of the current countdown
+    started_at: Option<DateTime<Utc>>,
+    records: Vec<SessionRecord>,
+    hook_events: Vec<HookEvent>,
+    /// Feedback for the last adjustment
+    flash: Option<Flash>,
 }
 
 impl CountdownTab {
 
+    pub fn new(label: String, config: &Config, clock: SharedClock) -> Self {
+        let mut countdown = Self {
+            label,
+            ..Self::with_clock(clock)
+        };
+        countdown.apply_config(config);
+        countdown
+    }
+
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            timer: Timer::with_clock(clock),
+            ..Self::default()
+        }
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.timer.clock().utc()
+    }
+
+    fn zone(&self) -> LocalZone {
+        self.timer.clock().zone()
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn set_label(&mut self, label: String) {
+        self.label = label;
+    }
+
+    pub fn state(&self) -> AppState {
+        self.state
+    }
+
+    /// Time left, or the typed duration while it is being set up.
+    pub fn remaining(&self) -> Duration {
+        if self.state.is_setup() {
+            self.parsed_input().map(|input| input.duration(self.now())).unwrap_or_default()
+        } else {
+            self.delta_option().unwrap_or_default()
+        }
+    }
+
+    /// Time counted so far, including any overtime.
+    pub fn elapsed(&self) -> Duration {
+        self.timer.elapsed()
+    }
+
+    /// How far the countdown ran past zero, while it counts overtime.
+    pub fn overtime(&self) -> Option<Duration> {
+        self.overtime.then(|| self.overdue())
+    }
+
+    /// How far the countdown is past zero, zero while there is time left.
+    fn overdue(&self) -> Duration {
+        match self.target {
+            Some(target) if self.state.is_running() => (self.now() - target.at).to_std().unwrap_or_default(),
+            _ => self.timer.elapsed().saturating_sub(self.init_duration),
+        }
+    }
+
+    /// The time shown, counting down or as negative overtime such as `-00:02:13.400`.
+    pub fn time_text(&self) -> String {
+        match self.overtime() {
+            Some(overtime) => format!("-{}", format_duration(overtime)),
+            None => format_duration(self.remaining()),
+        }
+    }
+
+    /// Stops the countdown for good, recording it as cancelled if it was started.
+    /// One in overtime counts as completed, with the overtime recorded.
+    pub fn discard(&mut self) {
+        if self.overtime {
+            self.dismiss();
+        } else {
+            self.stop();
+            self.record(Outcome::Cancelled);
+        }
+    }
+
+    /// Whether the countdown ran out, or was cleared, and has no duration left to start.
+    pub fn is_finished(&self) -> bool {
+        self.state.is_stopped() && self.init_duration.is_zero()
+    }
+
     fn clear(&mut self) {
         if self.state.is_stopped() || self.state.is_setup() {
-            self.index_counter = 0;
+            self.record(Outcome::Cancelled);
             self.init_duration = Duration::from_secs(0);
+            self.target = None;
+            self.overtime = false;
+            self.input.clear();
+            self.cursor = 0;
             self.timer.reset();
 
             self.update_can_start_value();
         }
     }
 
+    /// Ends overtime, recording the countdown as completed with how far it ran over.
+    fn dismiss(&mut self) {
+        let overtime = self.overtime();
+        if let Some(record) = self.record(Outcome::Completed) {
+            record.overtime = overtime;
+        }
+
+        self.stop();
+        self.clear();
+    }
+
+    /// Adds time to a started countdown, or takes it away, without pausing it.
+    /// Taking away more than is left ends it on the next tick.
+    fn adjust(&mut self, seconds: i64) {
+        if self.state.is_setup() || self.is_finished() {
+            return;
+        }
+
+        let amount = Duration::from_secs(seconds.unsigned_abs());
+        let delta = chrono::Duration::from_std(amount).unwrap_or_default();
+        if seconds >= 0 {
+            self.init_duration += amount;
+            self.target = self.target.map(|target| Target { at: target.at + delta, ..target });
+        } else {
+            self.init_duration = self.init_duration.saturating_sub(amount).max(self.timer.elapsed());
+            self.target = self.target.map(|target| Target { at: target.at - delta, ..target });
+        }
+
+        // adding enough time brings a countdown back from overtime
+        if self.overtime && self.delta_option().is_some_and(|delta| !delta.is_zero()) {
+            self.overtime = false;
+        }
+        self.flash = Some(Flash::new(format_adjustment(seconds), self.timer.clock().now()));
+    }
+
     fn toggle_start_pause(&mut self) {
+        if self.state.is_setup() {
+            self.apply_input();
+        }
+
         if !self.can_start {
             return;
         }
 
         if !self.state.is_running() {
             self.start();
+            self.hook(HookKind::Start, None);
         } else {
             self.stop();
+            self.hook(HookKind::Pause, None);
         }
     }
 
     fn start(&mut self) {
+        self.anchor_to_target();
         self.timer.start();
         self.state = AppState::Running;
+        let now = self.now();
+        self.started_at.get_or_insert(now);
     }
 
     fn stop(&mut self) {
         self.timer.pause();
+        self.anchor_to_target();
         self.state = AppState::Stopped;
     }
 
+    /// Lines `init_duration` up with the wall-clock target, so a paused countdown shows
+    /// the time that was left and a resumed one catches up on the pause.
+    fn anchor_to_target(&mut self) {
+        if let Some(target) = self.target {
+            let remaining = target.remaining(self.now()).unwrap_or_default();
+            self.init_duration = self.timer.elapsed() + remaining;
+        }
+    }
+
     fn edit(&mut self) {
+        if self.overtime {
+            self.dismiss();
+        }
         self.stop();
         self.clear();
         self.state = AppState::Setup;
         self.can_start = false;
     }
 
-    fn set_number(&mut self, num: u8) {
-        if !self.state.is_setup() {
+    /// Uses the typed duration or target, if it is valid, for the countdown.
+    fn apply_input(&mut self) {
+        match self.parsed_input() {
+            Ok(CountdownInput::Duration(duration)) if !duration.is_zero() => self.set_duration(duration),
+            Ok(CountdownInput::Target(target)) => self.set_target(target),
+            _ => (),
+        }
+    }
+
+    fn parsed_input(&self) -> Result<CountdownInput> {
+        if is_target(&self.input) {
+            parse_target(&self.input, self.now(), self.zone()).map(CountdownInput::Target)
+        } else {
+            parse_duration(&self.input).map(CountdownInput::Duration)
+        }
+    }
+
+    fn input_char(&mut self, c: char) {
+        if !self.state.is_setup() || !c.is_ascii() {
             return;
         }
 
-        let high_num: u64 = num.into();
+        self.input.insert(self.cursor, c);
+        self.cursor += 1;
+        self.update_can_start_value()
+    }
+
+    fn backspace(&mut self) {
+        if !self.state.is_setup() || self.cursor == 0 {
+            return;
+        }
 
-        let dur = match self.index_counter {
-            0 => Duration::from_secs(high_num),
-            1 => Duration::from_secs(high_num.wrapping_mul(10)),
-            2 => Duration::from_secs(high_num.wrapping_mul(60)),
-            3 => Duration::from_secs(high_num.wrapping_mul(600)),
-            4 => Duration::from_secs(high_num.wrapping_mul(3600)),
-            5 => Duration::from_secs(high_num.wrapping_mul(36000)),
-            _ => Duration::from_secs(0)
-        };
+        self.cursor -= 1;
+        self.input.remove(self.cursor);
+        self.update_can_start_value()
+    }
 
-        self.init_duration = self.init_duration.add(dur);
-        self.index_counter += 1;
-        if self.index_counter > 5 {
-            self.index_counter = 0;
+    fn delete(&mut self) {
+        if !self.state.is_setup() || self.cursor >= self.input.len() {
+            return;
         }
 
+        self.input.remove(self.cursor);
         self.update_can_start_value()
     }
 
+    fn cursor_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    fn cursor_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.input.len());
+    }
+
+    fn set_duration(&mut self, duration: Duration) {
+        self.stop();
+        self.record(Outcome::Cancelled);
+        self.timer.reset();
+        self.input.clear();
+        self.cursor = 0;
+        self.init_duration = duration;
+        self.target = None;
+        self.overtime = false;
+
+        self.update_can_start_value()
+    }
+
+    fn set_target(&mut self, target: Target) {
+        self.set_duration(target.remaining(self.now()).unwrap_or_default());
+        self.target = Some(target);
+    }
+
     fn update_can_start_value(&mut self) {
-        self.can_start = !self.init_duration.is_zero();
+        self.can_start = if self.state.is_setup() {
+            self.parsed_input().is_ok_and(|input| !input.duration(self.now()).is_zero())
+        } else {
+            !self.init_duration.is_zero()
+        };
     }
 
     fn check_delta(&mut self) {
-        if self.delta_option().is_none() {
-            let _ = Notification::new()
-                .summary("TimeMann Alert")
-                .body("Countdown finished!")
-                .show();
+        // a running countdown is over as soon as it reads zero
+        if !self.state.is_running() || self.overtime || self.delta_option().is_some_and(|delta| !delta.is_zero()) {
+            return;
+        }
 
+        self.notify(&self.notification.body);
+        self.hook(HookKind::Finish, Some(self.overdue()));
+        if self.allow_overtime {
+            self.overtime = true;
+        } else {
+            self.record(Outcome::Completed);
             self.stop();
             self.clear();
         }
     }
 
-    fn delta_option(&self) -> Option<Duration> {
-        self.init_duration.checked_sub(self.timer.elapsed())
+    /// The label, or what the countdown runs for when it has none.
+    fn display_label(&self) -> String {
+        match (self.label.as_str(), self.target) {
+            ("", Some(target)) => format!("until {}", target.display(self.zone())),
+            ("", None) => format_duration(self.init_duration),
+            (label, _) => label.to_string(),
+        }
+    }
+
+    /// Records the countdown that was started, if any, and forgets its start time.
+    fn record(&mut self, outcome: Outcome) -> Option<&mut SessionRecord> {
+        let started_at = self.started_at.take()?;
+        self.records.push(SessionRecord::new(
+            "countdown",
+            self.display_label(),
+            started_at,
+            self.timer.clock().utc(),
+            self.timer.elapsed().min(self.init_duration),
+            outcome,
+        ));
+        self.records.last_mut()
     }
 
-    fn delta(&mut self) -> Duration {
-        let diff = match self.delta_option() {
-            Some(d) => d,
-            None => Duration::from_secs(0)
+    fn hook(&mut self, kind: HookKind, overshoot: Option<Duration>) {
+        self.hook_events.push(HookEvent {
+            duration: Some(self.init_duration),
+            elapsed: Some(self.timer.elapsed().min(self.init_duration)),
+            overshoot,
+            ..HookEvent::new(kind, self.display_label())
+        });
+    }
+
+    /// Sends a desktop notification, prefixed with the label so concurrent timers can be told apart.
+    fn notify(&self, body: &str) {
+        let body = if self.label.is_empty() {
+            body.to_string()
+        } else {
+            format!("{}: {}", self.label, body)
         };
 
-        diff
+        self.notification.show(&body);
+    }
+
+    fn delta_option(&self) -> Option<Duration> {
+        match self.target {
+            // going by the wall clock keeps a target right across suspend and clock changes
+            Some(target) if self.state.is_running() => target.remaining(self.now()),
+            _ => self.init_duration.checked_sub(self.timer.elapsed()),
+        }
     }
 
     fn timer_paragraph(&mut self, area: Rect) -> BigText<'_> {
-        self.check_delta();
+        if self.overtime {
+            let style = Style::new().fg(theme().overtime).bg(theme().main_background);
+            return setup_big_text(self.time_text(), style, area.width);
+        }
 
         setup_big_timer_text(
-            format_duration(self.delta()), 
-            self.state, 
+            format_duration(self.remaining()),
+            self.state,
             area.width
         )
     }
 
+    fn input_paragraph(&self) -> Paragraph<'_> {
+        let (before, after) = self.input.split_at(self.cursor);
+        let mut after = after.chars();
+        let under_cursor = after.next().map_or(String::from(" "), String::from);
+
+        Paragraph::new(Line::from(vec![
+            "Duration: ".dim(),
+            before.into(),
+            under_cursor.reversed(),
+            after.as_str().into(),
+        ]))
+        .centered()
+    }
+
+    fn target_paragraph(&self) -> Paragraph<'_> {
+        if self.flash.as_ref().and_then(|flash| flash.text(self.timer.clock().now())).is_some() {
+            return Paragraph::new(flash_line(&self.flash, self.timer.clock().now())).centered();
+        }
+
+        if self.overtime {
+            return Paragraph::new("overtime".fg(theme().overtime)).centered();
+        }
+
+        let text = self.target.map(|target| format!("until {}", target.display(self.zone()))).unwrap_or_default();
+        Paragraph::new(text).dim().centered()
+    }
+
+    fn validation_paragraph(&self) -> Paragraph<'_> {
+        let line = if self.input.trim().is_empty() {
+            "e.g. 25m, 1h30m, 1:30:00, PT1H30M, until 17:30 or next monday 09:00".dim()
+        } else {
+            match self.parsed_input() {
+                Ok(CountdownInput::Duration(d)) if d.is_zero() => "duration must be longer than zero".red(),
+                Ok(CountdownInput::Duration(d)) => format!("= {}", format_duration(d)).green(),
+                Ok(CountdownInput::Target(target)) => format!(
+                    "= until {} (in {})",
+                    target.display(self.zone()),
+                    format_duration(target.remaining(self.now()).unwrap_or_default())
+                ).green(),
+                Err(err) => err.to_string().red(),
+            }
+        };
+
+        Paragraph::new(line).centered()
+    }
+
 }
 
 pub fn layout(area: Rect) -> Vec<Rect> {
     let layout = Layout::vertical(vec![
         Constraint::Percentage(100), // INGORE
         Constraint::Min(8), // timer
+        Constraint::Length(1), // input
+        Constraint::Length(1), // validation
         Constraint::Percentage(100), // INGORE
     ])
     .split(area);
 
-    layout[1..]
-        .iter()
-        .copied()
-        .collect()
+    layout[1..].to_vec()
 }
 
 impl AppTab for CountdownTab {
+    fn id(&self) -> &'static str {
+        "countdown"
+    }
+
+    fn title(&self) -> &'static str {
+        "Countdown"
+    }
+
+    fn icon(&self) -> &'static str {
+        "⏳"
+    }
+
     fn handle_message(&mut self, message: Message) {
+        if self.overtime && matches!(message, Message::ToggleStartPause | Message::Clear) {
+            self.dismiss();
+            return;
+        }
+
         match message {
             Message::ToggleStartPause => self.toggle_start_pause(),
             Message::Clear => self.clear(),
-            Message::SetNumber(num) => self.set_number(num),
+            Message::Input(c) => self.input_char(c),
+            Message::Backspace => self.backspace(),
+            Message::Delete => self.delete(),
+            Message::CursorLeft => self.cursor_left(),
+            Message::CursorRight => self.cursor_right(),
+            Message::SetDuration(duration) => self.set_duration(duration),
+            Message::SetTarget(target) => self.set_target(target),
             Message::Edit => self.edit(),
+            Message::Adjust(seconds) => self.adjust(seconds),
             _ => (),
         }
     }
-    
+
+    fn tick(&mut self) {
+        self.check_delta();
+    }
+
+    fn redraw_in(&self) -> Option<Duration> {
+        // a target being typed in already counts down
+        let typing_target = self.state.is_setup() && matches!(self.parsed_input(), Ok(CountdownInput::Target(_)));
+        let timer = match self.overtime() {
+            Some(overtime) => Some(next_change(overtime, false)),
+            None if self.state.is_running() || typing_target => Some(next_change(self.remaining(), true)),
+            None => None,
+        };
+        sooner(timer, self.flash.as_ref().and_then(|flash| flash.remaining(self.timer.clock().now())))
+    }
+
+    fn apply_config(&mut self, config: &Config) {
+        self.notification = config.notification.clone();
+        self.allow_overtime = config.countdown.overtime;
+    }
+
     fn render_paragraph(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
         let area = layout(*area);
         frame.render_widget(self.timer_paragraph(area[0]), area[0]);
+
+        if self.state.is_setup() {
+            frame.render_widget(self.input_paragraph(), area[1]);
+            frame.render_widget(self.validation_paragraph(), area[2]);
+        } else {
+            frame.render_widget(self.target_paragraph(), area[1]);
+        }
         Ok(())
     }
 
-    fn help_text(&mut self) -> Vec<Span> {
-        let enter_part = if self.can_start {
-            ( 
-                " Enter ".on_dark_gray().black().dim(), 
-                if !self.state.is_running() {
-                    " Start ".dim()
-                } else {
-                    " Pause ".dim()
-                }
-            )
+    fn status(&self) -> Option<TabStatus> {
+        let state = if self.overtime {
+            TimerState::Overtime
+        } else if self.state.is_setup() {
+            TimerState::Setup
+        } else if self.state.is_running() {
+            TimerState::Running
+        } else if self.is_finished() {
+            TimerState::Idle
         } else {
-            ( "".on_dark_gray().black().dim(), "".dim() )
+            TimerState::Paused
         };
 
-        let edit_part = if !self.state.is_setup() {
-            ( " E ".on_dark_gray().black().dim(), " Edit Mode ".dim() )       
-        } else {
-            ( "".on_dark_gray().black().dim(), "".dim() )
-        };
+        Some(TabStatus {
+            state,
+            label: self.label.clone(),
+            elapsed: Some(self.timer.elapsed()),
+            remaining: Some(self.remaining()),
+        })
+    }
 
-        let number_part = if self.state.is_setup() {
-            ( " 0-9 ".on_dark_gray().black().dim(), " Set Time ".dim() )           
-        } else {
-            ( "".on_dark_gray().black().dim(), "".dim() )
+    fn save_state(&self) -> Option<Value> {
+        let saved = SavedCountdown {
+            state: self.state,
+            timer: self.timer.snapshot(),
+            init_duration: self.init_duration,
+            input: self.input.clone(),
+            started_at: self.started_at,
+            label: self.label.clone(),
+            target: self.target,
+            overtime: self.overtime,
         };
+        serde_json::to_value(saved).ok()
+    }
 
-        let clear_part = if self.state.is_setup() && !self.init_duration.is_zero() {
-            ( " C ".on_dark_gray().black().dim(), " Clear ".dim() )
-        } else {
-            ( "".on_dark_gray().black().dim(), "".dim() )
-        };
+    fn restore_state(&mut self, state: Value) -> Result<Option<String>> {
+        let saved: SavedCountdown = serde_json::from_value(state)?;
+        self.state = saved.state;
+        self.timer = Timer::from_snapshot(saved.timer, self.timer.clock().clone());
+        self.init_duration = saved.init_duration;
+        self.input = saved.input;
+        self.cursor = self.input.len();
+        self.started_at = saved.started_at;
+        self.label = saved.label;
+        self.target = saved.target;
+        self.overtime = saved.overtime;
+        self.update_can_start_value();
+
+        // a countdown that ran out while the app was closed is reported instead of resumed,
+        // or resumed in overtime when that is enabled
+        if self.state.is_running() && !self.overtime && self.delta_option().is_none() {
+            let overdue = self.overdue();
+            let name = match self.label.as_str() {
+                "" => String::from("Countdown"),
+                label => format!("Countdown '{label}'"),
+            };
+            let notice = format!(
+                "{} finished {} ago, while timemann was closed",
+                name,
+                format_duration(overdue)
+            );
+            self.notify(&format!("{} ({})", self.notification.body, notice));
+            self.hook(HookKind::Finish, Some(overdue));
+
+            if self.allow_overtime {
+                self.overtime = true;
+                return Ok(Some(notice));
+            }
+            if let Some(record) = self.record(Outcome::Completed) {
+                record.ended_at -= chrono::Duration::from_std(overdue).unwrap_or_default();
+            }
+
+            self.stop();
+            self.clear();
+            return Ok(Some(notice));
+        }
+        Ok(None)
+    }
+
+    fn help_entries(&self) -> Vec<(Message, &'static str)> {
+        let mut entries = vec![];
+
+        if self.overtime {
+            entries.push((Message::ToggleStartPause, "Dismiss"));
+            entries.push((Message::Adjust(60), "Adjust"));
+            entries.push((Message::Adjust(-60), "Adjust"));
+            entries.push((Message::Edit, "Edit Mode"));
+            return entries;
+        }
+
+        if self.can_start {
+            entries.push((
+                Message::ToggleStartPause,
+                if !self.state.is_running() { "Start" } else { "Pause" },
+            ));
+        }
+
+        if !self.state.is_setup() && !self.is_finished() {
+            entries.push((Message::Adjust(60), "Adjust"));
+            entries.push((Message::Adjust(-60), "Adjust"));
+        }
+
+        if !self.state.is_setup() {
+            entries.push((Message::Edit, "Edit Mode"));
+        }
 
-        vec![
-            enter_part.0,
-            enter_part.1,
-            edit_part.0,
-            edit_part.1,
-            number_part.0,
-            number_part.1,
-            clear_part.0,
-            clear_part.1
-        ]
+        if self.state.is_setup() && !self.input.is_empty() {
+            entries.push((Message::Clear, "Clear"));
+        }
+
+        entries
+    }
+
+    fn take_records(&mut self) -> Vec<SessionRecord> {
+        std::mem::take(&mut self.records)
+    }
+
+    fn take_hook_events(&mut self) -> Vec<HookEvent> {
+        std::mem::take(&mut self.hook_events)
+    }
+
+    /// Bound keys keep working on an idle tab, and once something is typed they become
+    /// text too, so targets such as `next monday` can be spelled out.
+    fn text_input(&self) -> TextInput {
+        match (self.state.is_setup(), self.input.is_empty()) {
+            (true, true) => TextInput::Unbound,
+            (true, false) => TextInput::All,
+            (false, _) => TextInput::Off,
+        }
     }
 }
 
 impl Default for CountdownTab {
     fn default() -> Self {
         Self {
+            label: String::new(),
             state: AppState::Setup,
             timer: Timer::new(),
             init_duration: Duration::from_secs(0),
-            index_counter: 0,
-            can_start: false
+            target: None,
+            input: String::new(),
+            cursor: 0,
+            can_start: false,
+            notification: NotificationConfig::default(),
+            allow_overtime: false,
+            overtime: false,
+            started_at: None,
+            records: vec![],
+            hook_events: vec![],
+            flash: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::clock::{secs, Clock, FakeClock};
+    use crate::hooks::HookKind;
+
+    fn countdown(overtime: bool) -> (Arc<FakeClock>, CountdownTab) {
+        let clock = FakeClock::new();
+        let mut config = Config::default();
+        config.countdown.overtime = overtime;
+        let countdown = CountdownTab::new(String::new(), &config, SharedClock::new(clock.clone()));
+        (clock, countdown)
+    }
+
+    fn type_text(countdown: &mut CountdownTab, text: &str) {
+        for c in text.chars() {
+            countdown.handle_message(Message::Input(c));
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn starts_pauses_and_resumes() {
+        let (clock, mut countdown) = countdown(false);
+        countdown.handle_message(Message::SetDuration(secs(10)));
+        countdown.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(4));
+        assert_eq!(countdown.remaining(), secs(6));
+
+        countdown.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(30));
+        countdown.tick();
+        assert_eq!(countdown.remaining(), secs(6));
+        assert_eq!(countdown.status().unwrap().state, TimerState::Paused);
+
+        countdown.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(1));
+        assert_eq!(countdown.remaining(), secs(5));
+        assert_eq!(countdown.status().unwrap().state, TimerState::Running);
+
+        let kinds: Vec<_> = countdown.take_hook_events().iter().map(|event| event.kind).collect();
+        assert_eq!(kinds, [HookKind::Start, HookKind::Pause, HookKind::Start]);
+    }
+
+    #[test]
+    fn expires_at_zero() {
+        let (clock, mut countdown) = countdown(false);
+        countdown.handle_message(Message::SetDuration(secs(5)));
+        countdown.handle_message(Message::ToggleStartPause);
+
+        clock.advance(Duration::from_millis(4999));
+        countdown.tick();
+        assert!(!countdown.is_finished());
+
+        clock.advance(Duration::from_millis(1));
+        countdown.tick();
+        assert!(countdown.is_finished());
+        assert_eq!(countdown.remaining(), Duration::ZERO);
+
+        let records = countdown.take_records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].outcome, Outcome::Completed);
+        assert_eq!(records[0].duration, secs(5));
+
+        let finish = countdown.take_hook_events().pop().unwrap();
+        assert_eq!(finish.kind, HookKind::Finish);
+        assert_eq!(finish.duration, Some(secs(5)));
+        assert_eq!(finish.overshoot, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn reports_how_late_the_end_was_noticed() {
+        let (clock, mut countdown) = countdown(false);
+        countdown.handle_message(Message::SetDuration(secs(5)));
+        countdown.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(8));
+        countdown.tick();
+
+        let finish = countdown.take_hook_events().pop().unwrap();
+        assert_eq!(finish.overshoot, Some(secs(3)));
+    }
+
+    #[test]
+    fn counts_overtime_until_dismissed() {
+        let (clock, mut countdown) = countdown(true);
+        countdown.handle_message(Message::SetDuration(secs(5)));
+        countdown.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(5));
+        countdown.tick();
+        clock.advance(secs(2));
+
+        assert!(!countdown.is_finished());
+        assert_eq!(countdown.overtime(), Some(secs(2)));
+        assert_eq!(countdown.status().unwrap().state, TimerState::Overtime);
+        assert!(countdown.take_records().is_empty());
+
+        countdown.handle_message(Message::ToggleStartPause);
+        assert!(countdown.is_finished());
+        let records = countdown.take_records();
+        assert_eq!(records[0].outcome, Outcome::Completed);
+        assert_eq!(records[0].overtime, Some(secs(2)));
+    }
+
+    #[test]
+    fn adding_time_leaves_overtime() {
+        let (clock, mut countdown) = countdown(true);
+        countdown.handle_message(Message::SetDuration(secs(5)));
+        countdown.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(10));
+        countdown.tick();
+
+        countdown.handle_message(Message::Adjust(60));
+        assert_eq!(countdown.overtime(), None);
+        assert_eq!(countdown.remaining(), secs(55));
+    }
+
+    #[test]
+    fn counts_down_to_a_target() {
+        let (clock, mut countdown) = countdown(false);
+        let target = parse_target("09:01", clock.utc(), clock.zone()).unwrap();
+        countdown.handle_message(Message::SetTarget(target));
+        countdown.handle_message(Message::ToggleStartPause);
+        assert_eq!(countdown.remaining(), target.remaining(clock.utc()).unwrap());
+
+        clock.advance(target.remaining(clock.utc()).unwrap());
+        countdown.tick();
+        assert!(countdown.is_finished());
+    }
+
+    #[test]
+    fn edit_applies_a_typed_duration() {
+        let (clock, mut countdown) = countdown(false);
+        countdown.handle_message(Message::Edit);
+        assert_eq!(countdown.status().unwrap().state, TimerState::Setup);
+
+        type_text(&mut countdown, "1m30s");
+        assert_eq!(countdown.remaining(), secs(90));
+
+        countdown.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(30));
+        assert_eq!(countdown.remaining(), secs(60));
+    }
+
+    #[test]
+    fn edit_cancels_a_running_countdown() {
+        let (clock, mut countdown) = countdown(false);
+        countdown.handle_message(Message::SetDuration(secs(60)));
+        countdown.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(20));
+
+        countdown.handle_message(Message::Edit);
+        assert!(countdown.state().is_setup());
+        let records = countdown.take_records();
+        assert_eq!(records[0].outcome, Outcome::Cancelled);
+        assert_eq!(records[0].duration, secs(20));
+    }
+
+    #[test]
+    fn idle_countdown_never_expires() {
+        let (clock, mut countdown) = countdown(false);
+        clock.advance(secs(5));
+        countdown.tick();
+        countdown.handle_message(Message::SetDuration(secs(5)));
+        clock.advance(secs(10));
+        countdown.tick();
+
+        assert_eq!(countdown.remaining(), secs(5));
+        assert!(countdown.take_records().is_empty());
+        assert!(countdown.take_hook_events().is_empty());
+    }
+
+    #[test]
+    fn invalid_input_does_not_start() {
+        let (clock, mut countdown) = countdown(false);
+        countdown.handle_message(Message::Edit);
+        type_text(&mut countdown, "soon");
+        countdown.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(1));
+
+        assert!(countdown.state().is_setup());
+        assert_eq!(countdown.elapsed(), Duration::ZERO);
+
+        countdown.handle_message(Message::Clear);
+        type_text(&mut countdown, "0s");
+        countdown.handle_message(Message::ToggleStartPause);
+        assert!(countdown.state().is_setup());
+    }
+
+    #[test]
+    fn clear_resets_a_paused_countdown() {
+        let (clock, mut countdown) = countdown(false);
+        countdown.handle_message(Message::SetDuration(secs(60)));
+        countdown.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(10));
+
+        // clearing only works once paused
+        countdown.handle_message(Message::Clear);
+        assert_eq!(countdown.remaining(), secs(50));
+
+        countdown.handle_message(Message::ToggleStartPause);
+        countdown.handle_message(Message::Clear);
+        assert!(countdown.is_finished());
+        assert_eq!(countdown.elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,318 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+
+/// Parses a human-friendly duration. Accepted forms are:
+///
+/// - unit sequences such as `25m`, `1h30m`, `1h 30m`, `90s` or `2.5m`
+/// - clock notation `h:mm:ss` or `m:ss`, e.g. `1:30:00`
+/// - ISO 8601 durations such as `PT1H30M`
+///
+/// A bare number is read as seconds.
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    if input.is_empty() {
+        bail!("duration is empty");
+    }
+
+    if input.starts_with(['P', 'p']) {
+        parse_iso8601(input)
+    } else if input.contains(':') {
+        parse_clock(input)
+    } else if let Ok(secs) = input.parse::<f64>() {
+        seconds_to_duration(secs)
+    } else {
+        parse_units(input)
+    }
+}
+
+fn parse_units(input: &str) -> Result<Duration> {
+    let mut total = 0.0;
+    let mut seen_units: Vec<&str> = vec![];
+    let mut chars = input.chars().peekable();
+
+    while chars.peek().is_some() {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut number = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+            number.push(c);
+        }
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut unit = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_alphabetic()) {
+            unit.push(c.to_ascii_lowercase());
+        }
+
+        match (number.is_empty(), unit.is_empty()) {
+            (true, true) => match chars.next() {
+                Some(c) => bail!("unexpected character '{c}'"),
+                None => break,
+            },
+            (true, false) => bail!("missing number before '{unit}'"),
+            (false, true) => bail!("missing unit after '{number}', try s, m or h"),
+            (false, false) => (),
+        }
+
+        let (name, unit_secs) = match unit.as_str() {
+            "d" | "day" | "days" => ("d", 86400.0),
+            "h" | "hr" | "hrs" | "hour" | "hours" => ("h", 3600.0),
+            "m" | "min" | "mins" | "minute" | "minutes" => ("m", 60.0),
+            "s" | "sec" | "secs" | "second" | "seconds" => ("s", 1.0),
+            "ms" => ("ms", 0.001),
+            _ => bail!("unknown unit '{unit}'"),
+        };
+
+        if seen_units.contains(&name) {
+            bail!("unit '{name}' given more than once");
+        }
+        seen_units.push(name);
+
+        total += parse_number(&number)? * unit_secs;
+    }
+
+    seconds_to_duration(total)
+}
+
+fn parse_clock(input: &str) -> Result<Duration> {
+    let parts: Vec<&str> = input.split(':').map(str::trim).collect();
+
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [m, s] => ("0", *m, *s),
+        [h, m, s] => (*h, *m, *s),
+        _ => bail!("expected m:ss or h:mm:ss"),
+    };
+
+    let hours: u64 = parse_whole(hours, "hours")?;
+    let minutes: u64 = parse_whole(minutes, "minutes")?;
+    let seconds = parse_number(seconds)?;
+
+    if parts.len() == 3 && minutes >= 60 {
+        bail!("minutes must be below 60");
+    }
+    if seconds >= 60.0 {
+        bail!("seconds must be below 60");
+    }
+
+    seconds_to_duration(hours.saturating_mul(3600).saturating_add(minutes.saturating_mul(60)) as f64 + seconds)
+}
+
+fn parse_iso8601(input: &str) -> Result<Duration> {
+    let upper = input.to_ascii_uppercase();
+    let mut rest = &upper[1..];
+    if rest.trim_start_matches('T').is_empty() {
+        bail!("ISO 8601 duration has no components");
+    }
+
+    let mut total = 0.0;
+    let mut in_time = false;
+
+    while !rest.is_empty() {
+        if let Some(stripped) = rest.strip_prefix('T') {
+            if in_time {
+                bail!("'T' given more than once");
+            }
+            in_time = true;
+            rest = stripped;
+            continue;
+        }
+
+        let end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+            .context("ISO 8601 component is missing its designator")?;
+        if end == 0 {
+            bail!("missing number before '{}'", rest.chars().next().unwrap_or_default());
+        }
+
+        let value = parse_number(&rest[..end].replace(',', "."))?;
+        let designator = rest[end..].chars().next().unwrap_or_default();
+
+        total += value * match (in_time, designator) {
+            (false, 'W') => 604800.0,
+            (false, 'D') => 86400.0,
+            (false, 'Y') | (false, 'M') => bail!("years and months are not supported"),
+            (true, 'H') => 3600.0,
+            (true, 'M') => 60.0,
+            (true, 'S') => 1.0,
+            (false, 'H') | (false, 'S') => bail!("'{designator}' must come after 'T'"),
+            _ => bail!("unknown designator '{designator}'"),
+        };
+
+        rest = &rest[end + designator.len_utf8()..];
+    }
+
+    seconds_to_duration(total)
+}
+
+fn parse_number(input: &str) -> Result<f64> {
+    let value: f64 = input
+        .parse()
+        .with_context(|| format!("'{input}' is not a number"))?;
+    if !value.is_finite() || value < 0.0 {
+        bail!("'{input}' is not a valid amount");
+    }
+    Ok(value)
+}
+
+fn parse_whole(input: &str, name: &str) -> Result<u64> {
+    input
+        .parse()
+        .with_context(|| format!("{name} must be a whole number"))
+}
+
+fn seconds_to_duration(secs: f64) -> Result<Duration> {
+    if !secs.is_finite() || secs < 0.0 {
+        bail!("duration must be a positive amount of time");
+    }
+
+    // round to whole milliseconds so `2.5m` is exactly 150 seconds
+    let millis = (secs * 1000.0).round();
+    if millis > u64::MAX as f64 {
+        bail!("duration is too large");
+    }
+    Ok(Duration::from_millis(millis as u64))
+}
+
+/// Serde helper for config fields written as durations, e.g. `work = "25m"`.
+pub fn deserialize_duration<'de, D>(deserializer: D) -> std::result::Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let text = <String as serde::Deserialize>::deserialize(deserializer)?;
+    parse_duration(&text).map_err(serde::de::Error::custom)
+}
+
+/// Serializes a duration as whole milliseconds, for fields such as `duration_ms`.
+pub mod millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis().try_into().unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+/// Like `millis`, for an optional duration.
+pub mod optional_millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => super::millis::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Option::<u64>::deserialize(deserializer).map(|millis| millis.map(Duration::from_millis))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn error(input: &str) -> String {
+        parse_duration(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn unit_sequences() {
+        for (input, expected) in [
+            ("25m", ms(25 * 60_000)),
+            ("1h30m", ms(90 * 60_000)),
+            ("1h 30m", ms(90 * 60_000)),
+            ("1 hour 30 mins", ms(90 * 60_000)),
+            ("2d4h", ms(52 * 3_600_000)),
+            ("90s", ms(90_000)),
+            ("1M 5S", ms(65_000)),
+            ("1s250ms", ms(1250)),
+            ("  10s  ", ms(10_000)),
+        ] {
+            assert_eq!(parse_duration(input).unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn clock_notation() {
+        for (input, expected) in [
+            ("1:30:00", ms(90 * 60_000)),
+            ("0:00:05", ms(5000)),
+            ("1:30", ms(90_000)),
+            ("90:00", ms(90 * 60_000)),
+            ("0:01.5", ms(1500)),
+        ] {
+            assert_eq!(parse_duration(input).unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn iso_8601() {
+        for (input, expected) in [
+            ("PT1H30M", ms(90 * 60_000)),
+            ("pt45s", ms(45_000)),
+            ("P1DT2H", ms(26 * 3_600_000)),
+            ("P1W", ms(7 * 86_400_000)),
+            ("PT0,5M", ms(30_000)),
+        ] {
+            assert_eq!(parse_duration(input).unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn bare_numbers_are_seconds() {
+        assert_eq!(parse_duration("90").unwrap(), ms(90_000));
+        assert_eq!(parse_duration("1.5").unwrap(), ms(1500));
+        assert_eq!(parse_duration("0").unwrap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn rounds_to_whole_milliseconds() {
+        assert_eq!(parse_duration("2.5m").unwrap(), ms(150_000));
+        assert_eq!(parse_duration("0.1h").unwrap(), ms(360_000));
+        assert_eq!(parse_duration("1.0004s").unwrap(), ms(1000));
+        assert_eq!(parse_duration("0.0005s").unwrap(), ms(1));
+    }
+
+    #[test]
+    fn errors() {
+        for (input, expected) in [
+            ("", "duration is empty"),
+            ("   ", "duration is empty"),
+            ("5x", "unknown unit 'x'"),
+            ("m", "missing number before 'm'"),
+            ("5 10m", "missing unit after '5', try s, m or h"),
+            ("1m 2m", "unit 'm' given more than once"),
+            ("5m!", "unexpected character '!'"),
+            ("1.2.3s", "'1.2.3' is not a number"),
+            ("-5", "duration must be a positive amount of time"),
+            ("1:60:00", "minutes must be below 60"),
+            ("1:60", "seconds must be below 60"),
+            ("1:2:3:4", "expected m:ss or h:mm:ss"),
+            ("x:30", "minutes must be a whole number"),
+            ("P", "ISO 8601 duration has no components"),
+            ("PT", "ISO 8601 duration has no components"),
+            ("P1M", "years and months are not supported"),
+            ("P1H", "'H' must come after 'T'"),
+            ("PT1H30", "ISO 8601 component is missing its designator"),
+            ("PTT1H", "'T' given more than once"),
+            ("PT1X", "unknown designator 'X'"),
+        ] {
+            assert_eq!(error(input), expected, "{input:?}");
+        }
+    }
+}
@@ -1,30 +1,37 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use crate::clock::SharedClock;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FpsCounter {
     start_time: Instant,
     frames: u32,
+    last_frame: Instant,
     pub fps: f64,
+    clock: SharedClock,
 }
 
 impl Default for FpsCounter {
     fn default() -> Self {
-        Self::new()
+        Self::new(SharedClock::default())
     }
 }
 
 impl FpsCounter {
-    fn new() -> Self {
+    pub fn new(clock: SharedClock) -> Self {
         Self {
-            start_time: Instant::now(),
+            start_time: clock.now(),
             frames: 0,
+            last_frame: clock.now(),
             fps: 0.0,
+            clock,
         }
     }
 
     pub fn tick(&mut self) {
         self.frames += 1;
-        let now = Instant::now();
+        let now = self.clock.now();
+        self.last_frame = now;
         let elapsed = (now - self.start_time).as_secs_f64();
         if elapsed >= 1.0 {
             self.fps = self.frames as f64 / elapsed;
@@ -32,4 +39,19 @@ impl FpsCounter {
             self.frames = 0;
         }
     }
+
+    /// Drops the rate to 0 once no frame was drawn for a second, as `tick` only updates it
+    /// when one is. Returns whether the rate changed.
+    pub fn decay(&mut self) -> bool {
+        let now = self.clock.now();
+        if now.saturating_duration_since(self.last_frame) < Duration::from_secs(1) {
+            return false;
+        }
+        // the next frame starts a new second instead of averaging over the idle time
+        self.start_time = now;
+        self.frames = 0;
+        let changed = self.fps != 0.0;
+        self.fps = 0.0;
+        changed
+    }
 }
@@ -0,0 +1,231 @@
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use serde_json::json;
+use tokio::select;
+use tokio::time::{sleep_until, Instant};
+
+use crate::cli::{Cli, Command, Output};
+use crate::clock::SharedClock;
+use crate::config::Config;
+use crate::countdown_tab::CountdownTab;
+use crate::history;
+use crate::hooks::{HookEvent, Hooks};
+use crate::msg::Message;
+use crate::stopwatch_tab::StopwatchTab;
+use crate::tabs::AppTab;
+use crate::ui::{format_duration, set_time_format, TimeFormat};
+
+
+/// How often progress is printed.
+const TICK: Duration = Duration::from_secs(1);
+
+/// Exit status after Ctrl+C or SIGTERM, as shells report a process killed by SIGINT.
+const INTERRUPTED: u8 = 130;
+
+/// The timer a headless run drives, started from the subcommand.
+enum Headless {
+    Countdown(CountdownTab),
+    Stopwatch(StopwatchTab),
+}
+
+impl Headless {
+    fn from_command(command: Option<&Command>, config: &Config) -> Result<Self> {
+        // nobody is around to dismiss overtime, so a headless countdown always ends at zero
+        let mut config = config.clone();
+        config.countdown.overtime = false;
+
+        let mut headless = match command {
+            Some(Command::Countdown { duration: Some(duration), .. }) => {
+                let mut countdown = CountdownTab::new(String::new(), &config, SharedClock::default());
+                countdown.handle_message(Message::SetDuration(*duration));
+                Headless::Countdown(countdown)
+            }
+            Some(Command::Countdown { until: Some(target), .. }) => {
+                let mut countdown = CountdownTab::new(String::new(), &config, SharedClock::default());
+                countdown.handle_message(Message::SetTarget(*target));
+                Headless::Countdown(countdown)
+            }
+            Some(Command::Stopwatch) => Headless::Stopwatch(StopwatchTab::default()),
+            _ => bail!("--plain and --json-lines need `countdown <DURATION>`, `countdown --until <TIME>` or `stopwatch`"),
+        };
+
+        headless.tab().handle_message(Message::ToggleStartPause);
+        Ok(headless)
+    }
+
+    fn tab(&mut self) -> &mut dyn AppTab {
+        match self {
+            Headless::Countdown(countdown) => countdown,
+            Headless::Stopwatch(stopwatch) => stopwatch,
+        }
+    }
+
+    /// Hook events of the timer, with the name of its tab.
+    fn hook_events(&mut self) -> Vec<(String, HookEvent)> {
+        let name = self.name().to_string();
+        self.tab()
+            .take_hook_events()
+            .into_iter()
+            .map(|event| (name.clone(), event))
+            .collect()
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Headless::Countdown(_) => "countdown",
+            Headless::Stopwatch(_) => "stopwatch",
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        match self {
+            Headless::Countdown(countdown) => countdown.elapsed(),
+            Headless::Stopwatch(stopwatch) => stopwatch.elapsed(),
+        }
+    }
+
+    /// Time left on a countdown, `None` for a stopwatch.
+    fn remaining(&self) -> Option<Duration> {
+        match self {
+            Headless::Countdown(countdown) => Some(countdown.remaining()),
+            Headless::Stopwatch(_) => None,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        match self {
+            Headless::Countdown(countdown) => countdown.is_finished(),
+            Headless::Stopwatch(_) => false,
+        }
+    }
+
+    /// Ends an interrupted run, so it is recorded as cancelled or, for a stopwatch, completed.
+    fn interrupt(&mut self) {
+        match self {
+            Headless::Countdown(countdown) => countdown.discard(),
+            Headless::Stopwatch(stopwatch) => {
+                stopwatch.handle_message(Message::ToggleStartPause);
+                stopwatch.handle_message(Message::Clear);
+            }
+        }
+    }
+
+    /// Prints the progress as `state`, one self-updating line or one JSON object.
+    fn print(&self, output: Output, state: &str, elapsed: Duration, remaining: Option<Duration>) -> Result<()> {
+        let mut stdout = io::stdout().lock();
+        match output {
+            Output::Plain => {
+                // a countdown shows whole seconds rounded up, so it reads 00:00:01 until it ends
+                let shown = match remaining {
+                    Some(remaining) => Duration::from_secs(remaining.as_secs_f64().ceil() as u64),
+                    None => elapsed,
+                };
+                write!(stdout, "\r{}", format_duration(shown))?;
+                match state {
+                    "running" => (),
+                    "completed" => writeln!(stdout, " done")?,
+                    _ => writeln!(stdout, " {state}")?,
+                }
+            }
+            Output::JsonLines => {
+                let mut line = json!({
+                    "time": Utc::now(),
+                    "tab": self.name(),
+                    "state": state,
+                    "elapsed_ms": elapsed.as_millis() as u64,
+                });
+                if let Some(remaining) = remaining {
+                    line["remaining_ms"] = json!(remaining.as_millis() as u64);
+                }
+                writeln!(stdout, "{line}")?;
+            }
+        }
+        stdout.flush().context("failed to write to stdout")
+    }
+}
+
+/// Runs the countdown or stopwatch from the command line without the interface, printing
+/// its progress to stdout. Exits with success once a countdown completes and with
+/// status 130 when interrupted.
+pub async fn run(cli: &Cli, output: Output) -> Result<ExitCode> {
+    let config = match cli.config.clone().or_else(Config::default_path) {
+        Some(path) => Config::load(&path)?,
+        None => Config::default(),
+    };
+    set_time_format(TimeFormat::Seconds);
+
+    let mut headless = Headless::from_command(cli.command.as_ref(), &config)?;
+    let mut hooks = Hooks::default();
+    let interrupted = interrupted();
+    tokio::pin!(interrupted);
+
+    let mut next_print = Instant::now();
+    let status = loop {
+        // wake up for the next print, or earlier when the countdown ends before it
+        let wake = match headless.remaining() {
+            Some(remaining) => next_print.min(Instant::now() + remaining),
+            None => next_print,
+        };
+
+        hooks.fire(&config.hooks, headless.hook_events());
+        select! {
+            _ = sleep_until(wake) => (),
+            _ = &mut interrupted => {
+                let (elapsed, remaining) = (headless.elapsed(), headless.remaining());
+                headless.interrupt();
+                headless.print(output, "interrupted", elapsed, remaining)?;
+                break ExitCode::from(INTERRUPTED);
+            }
+        }
+
+        let (elapsed, remaining) = (headless.elapsed(), headless.remaining());
+        headless.tab().tick();
+        if headless.is_finished() {
+            headless.print(output, "completed", elapsed, Some(Duration::ZERO))?;
+            break ExitCode::SUCCESS;
+        }
+
+        if Instant::now() >= next_print {
+            headless.print(output, "running", elapsed, remaining)?;
+            next_print += TICK;
+        }
+    };
+
+    if let Some(path) = config.history_path() {
+        let records = headless.tab().take_records();
+        if !records.is_empty() {
+            history::append(&path, &records)?;
+        }
+    }
+
+    hooks.fire(&config.hooks, headless.hook_events());
+    hooks.wait().await;
+    Ok(status)
+}
+
+/// Completes on Ctrl+C or, on Unix, SIGTERM.
+async fn interrupted() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => select! {
+                _ = tokio::signal::ctrl_c() => (),
+                _ = terminate.recv() => (),
+            },
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
@@ -0,0 +1,120 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::EnumIs;
+
+use crate::duration::{millis, optional_millis};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIs, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Completed,
+    Cancelled,
+}
+
+/// One finished stopwatch session, countdown, pomodoro phase or workout,
+/// stored as a line of `$XDG_DATA_HOME/timemann/history.jsonl`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    #[serde(rename = "duration_ms", with = "millis")]
+    pub duration: Duration,
+    /// Keymap context of the tab that recorded it, e.g. `countdown`
+    pub tab: String,
+    pub label: String,
+    pub outcome: Outcome,
+    /// How far a countdown ran past zero before it was dismissed
+    #[serde(rename = "overtime_ms", default, skip_serializing_if = "Option::is_none", with = "optional_millis")]
+    pub overtime: Option<Duration>,
+}
+
+impl SessionRecord {
+    /// A record of a session that ended at `ended_at`, usually the clock's current time.
+    pub fn new(
+        tab: &str,
+        label: impl Into<String>,
+        started_at: DateTime<Utc>,
+        ended_at: DateTime<Utc>,
+        duration: Duration,
+        outcome: Outcome,
+    ) -> Self {
+        Self {
+            started_at,
+            ended_at,
+            duration,
+            tab: tab.to_string(),
+            label: label.into(),
+            outcome,
+            overtime: None,
+        }
+    }
+
+    /// Time spent on the session, including any overtime.
+    pub fn total(&self) -> Duration {
+        self.duration + self.overtime.unwrap_or_default()
+    }
+}
+
+/// Default location of the history file, under `$XDG_DATA_HOME/timemann`.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("timemann").join("history.jsonl"))
+}
+
+/// Appends records to the history file, creating it if needed.
+pub fn append(path: &Path, records: &[SessionRecord]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open history file {}", path.display()))?;
+
+    let mut text = String::new();
+    for record in records {
+        text.push_str(&serde_json::to_string(record)?);
+        text.push('\n');
+    }
+    file.write_all(text.as_bytes())
+        .with_context(|| format!("failed to write history file {}", path.display()))
+}
+
+/// Reads every record, oldest first. Lines that cannot be parsed are skipped and counted.
+pub fn load(path: &Path) -> Result<(Vec<SessionRecord>, usize)> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok((vec![], 0)),
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to read history file {}", path.display()))
+        }
+    };
+
+    let mut records = vec![];
+    let mut skipped = 0;
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(_) => skipped += 1,
+        }
+    }
+    Ok((records, skipped))
+}
+
+/// Replaces the whole history file, used after deleting records.
+pub fn save_all(path: &Path, records: &[SessionRecord]) -> Result<()> {
+    let temp_path = path.with_extension("jsonl.tmp");
+    if temp_path.exists() {
+        fs::remove_file(&temp_path)?;
+    }
+    append(&temp_path, records)?;
+    fs::rename(&temp_path, path).with_context(|| format!("failed to write history file {}", path.display()))
+}
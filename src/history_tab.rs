@@ -0,0 +1,272 @@
+use anyhow::Result;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+
+use crate::clock::SharedClock;
+use crate::colors::theme;
+use crate::config::Config;
+use crate::history::{self, SessionRecord};
+use crate::msg::Message;
+use crate::tabs::AppTab;
+use crate::ui::format_duration;
+use crate::watcher::FileWatcher;
+
+
+/// Tabs that record sessions, in the order the filter cycles through them.
+const FILTERS: &[&str] = &["stopwatch", "countdown", "timers", "pomodoro", "intervals"];
+
+#[derive(Debug, Default, Clone)]
+pub struct HistoryTab {
+    /// Records as stored in the history file, oldest first
+    records: Vec<SessionRecord>,
+    /// Only records of this tab are listed, all of them when `None`
+    filter: Option<&'static str>,
+    table_state: TableState,
+    watcher: Option<FileWatcher>,
+    error: Option<String>,
+    /// The file was reloaded since the screen was last told
+    reloaded: bool,
+    clock: SharedClock,
+}
+
+impl HistoryTab {
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            clock,
+            ..Self::default()
+        }
+    }
+
+    /// Indexes into `records` of the listed sessions, newest first.
+    fn visible(&self) -> Vec<usize> {
+        (0..self.records.len())
+            .rev()
+            .filter(|&index| self.filter.is_none_or(|tab| self.records[index].tab == tab))
+            .collect()
+    }
+
+    fn reload(&mut self) {
+        let Some(path) = self.watcher.as_ref().map(|watcher| watcher.path().to_path_buf()) else {
+            return;
+        };
+
+        match history::load(&path) {
+            Ok((records, skipped)) => {
+                self.records = records;
+                self.error = match skipped {
+                    0 => None,
+                    1 => Some(String::from("1 line of the history file could not be read")),
+                    count => Some(format!("{count} lines of the history file could not be read")),
+                };
+            }
+            Err(err) => self.error = Some(format!("{err:#}")),
+        }
+        self.clamp_selection();
+    }
+
+    fn clamp_selection(&mut self) {
+        let count = self.visible().len();
+        let selected = match self.table_state.selected() {
+            _ if count == 0 => None,
+            Some(selected) => Some(selected.min(count - 1)),
+            None => Some(0),
+        };
+        self.table_state.select(selected);
+    }
+
+    fn select_previous(&mut self) {
+        if let Some(selected) = self.table_state.selected() {
+            self.table_state.select(Some(selected.saturating_sub(1)));
+        }
+    }
+
+    fn select_next(&mut self) {
+        let count = self.visible().len();
+        if let Some(selected) = self.table_state.selected() {
+            self.table_state.select(Some((selected + 1).min(count.saturating_sub(1))));
+        }
+    }
+
+    fn cycle_filter(&mut self) {
+        self.filter = match self.filter {
+            None => FILTERS.first().copied(),
+            Some(tab) => FILTERS
+                .iter()
+                .position(|&filter| filter == tab)
+                .and_then(|index| FILTERS.get(index + 1))
+                .copied(),
+        };
+        self.table_state.select(Some(0));
+        self.clamp_selection();
+    }
+
+    fn delete_selected(&mut self) {
+        let Some(index) = self.table_state.selected().and_then(|selected| self.visible().get(selected).copied()) else {
+            return;
+        };
+        let Some(path) = self.watcher.as_ref().map(|watcher| watcher.path().to_path_buf()) else {
+            return;
+        };
+
+        let removed = self.records.remove(index);
+        if let Err(err) = history::save_all(&path, &self.records) {
+            self.records.insert(index, removed);
+            self.error = Some(format!("{err:#}"));
+            return;
+        }
+        // the rewrite is ours, no need to read it back
+        if let Some(watcher) = self.watcher.as_mut() {
+            watcher.written();
+        }
+        self.clamp_selection();
+    }
+
+    fn summary_paragraph(&self) -> Paragraph<'_> {
+        let visible = self.visible();
+        let total = visible.iter().map(|&index| self.records[index].total()).sum();
+        let sessions = match visible.len() {
+            1 => String::from("1 session"),
+            count => format!("{count} sessions"),
+        };
+
+        Paragraph::new(Line::from(vec![
+            "Showing: ".dim(),
+            self.filter.unwrap_or("all").into(),
+            format!("  ·  {sessions}  ·  {} in total", format_duration(total)).dim(),
+        ]))
+        .centered()
+    }
+
+    fn records_table(&self) -> Table<'_> {
+        let rows = self.visible().into_iter().map(|index| {
+            let record = &self.records[index];
+            let outcome = match record.overtime {
+                Some(overtime) => Span::from(format!("-{}", format_duration(overtime))).fg(theme().overtime),
+                None if record.outcome.is_completed() => Span::from("completed").green(),
+                None => Span::from("cancelled").red(),
+            };
+
+            Row::new(vec![
+                Cell::from(record.started_at.with_timezone(&self.clock.zone()).format("%Y-%m-%d %H:%M").to_string()),
+                Cell::from(record.tab.as_str()),
+                Cell::from(record.label.as_str()),
+                Cell::from(format_duration(record.duration)),
+                Cell::from(outcome),
+            ])
+        });
+
+        let widths = [
+            Constraint::Length(16),
+            Constraint::Length(9),
+            Constraint::Min(12),
+            Constraint::Length(16),
+            Constraint::Length(17),
+        ];
+
+        Table::new(rows, widths)
+            .header(Row::new(vec!["Started", "Tab", "Label", "Duration", "Outcome"]).bold())
+            .column_spacing(2)
+            .highlight_style(Style::new().reversed())
+            .block(Block::default().borders(Borders::TOP).dim())
+            .bg(theme().main_background)
+    }
+
+    fn error_paragraph(&self) -> Paragraph<'_> {
+        Paragraph::new(self.error.as_deref().unwrap_or_default().red()).centered()
+    }
+
+}
+
+pub fn layout(area: Rect) -> Vec<Rect> {
+    let layout = Layout::vertical(vec![
+        Constraint::Length(1), // INGORE
+        Constraint::Length(1), // summary
+        Constraint::Min(0), // records
+        Constraint::Length(1), // error
+    ])
+    .split(area);
+
+    let table = Layout::horizontal(vec![
+        Constraint::Percentage(10),
+        Constraint::Percentage(80),
+        Constraint::Percentage(10),
+    ])
+    .split(layout[2]);
+
+    vec![layout[1], table[1], layout[3]]
+}
+
+impl AppTab for HistoryTab {
+    fn id(&self) -> &'static str {
+        "history"
+    }
+
+    fn title(&self) -> &'static str {
+        "History"
+    }
+
+    fn icon(&self) -> &'static str {
+        "📜"
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::ScrollUp => self.select_previous(),
+            Message::ScrollDown => self.select_next(),
+            Message::Delete => self.delete_selected(),
+            Message::Filter => self.cycle_filter(),
+            _ => (),
+        }
+    }
+
+    fn tick(&mut self) {
+        if self.watcher.as_mut().is_some_and(FileWatcher::changed) {
+            self.reload();
+            self.reloaded = true;
+        }
+    }
+
+    fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.reloaded)
+    }
+
+    fn apply_config(&mut self, config: &Config) {
+        let path = config.history_path();
+        if path.as_deref() != self.watcher.as_ref().map(FileWatcher::path) {
+            self.watcher = path.map(|path| FileWatcher::new(path, self.clock.clone()));
+            self.reload();
+        }
+    }
+
+    fn render_paragraph(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
+        let area = layout(*area);
+        frame.render_widget(self.summary_paragraph(), area[0]);
+
+        if self.visible().is_empty() {
+            frame.render_widget(Paragraph::new("No sessions recorded yet").dim().centered(), area[1]);
+        } else {
+            let mut table_state = self.table_state.clone();
+            frame.render_stateful_widget(self.records_table(), area[1], &mut table_state);
+            *self.table_state.offset_mut() = table_state.offset();
+        }
+
+        frame.render_widget(self.error_paragraph(), area[2]);
+        Ok(())
+    }
+
+    fn help_entries(&self) -> Vec<(Message, &'static str)> {
+        let mut entries = vec![];
+
+        if self.visible().len() > 1 {
+            entries.push((Message::ScrollUp, "Select"));
+            entries.push((Message::ScrollDown, "Select"));
+        }
+
+        if self.table_state.selected().is_some() {
+            entries.push((Message::Delete, "Delete"));
+        }
+
+        entries.push((Message::Filter, "Filter"));
+        entries
+    }
+}
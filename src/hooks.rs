@@ -0,0 +1,181 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use chrono::Local;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::task::JoinSet;
+
+use crate::config::HooksConfig;
+use crate::ui::format_duration;
+
+
+/// What happened to a timer, each with its own hook in `[hooks]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    Start,
+    Pause,
+    Finish,
+    Lap,
+    PhaseChange,
+}
+
+impl HookKind {
+    /// Name of the hook in the config, also passed to it as `TIMEMANN_EVENT`.
+    pub fn name(self) -> &'static str {
+        match self {
+            HookKind::Start => "on_start",
+            HookKind::Pause => "on_pause",
+            HookKind::Finish => "on_finish",
+            HookKind::Lap => "on_lap",
+            HookKind::PhaseChange => "on_phase_change",
+        }
+    }
+}
+
+/// A timer event reported by a tab, passed to its hook as environment variables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookEvent {
+    pub kind: HookKind,
+    /// e.g. the timer's name, or the phase that begins on a phase change
+    pub label: String,
+    /// Planned length of the countdown, phase or interval, or the time of a lap
+    pub duration: Option<Duration>,
+    pub elapsed: Option<Duration>,
+    /// How late a finish was noticed, e.g. because the app was closed
+    pub overshoot: Option<Duration>,
+}
+
+impl HookEvent {
+    pub fn new(kind: HookKind, label: impl Into<String>) -> Self {
+        Self {
+            kind,
+            label: label.into(),
+            duration: None,
+            elapsed: None,
+            overshoot: None,
+        }
+    }
+
+    /// `TIMEMANN_*` variables describing the event, times both formatted and in milliseconds.
+    fn env(&self, tab: &str) -> Vec<(String, String)> {
+        let mut env = vec![
+            (String::from("TIMEMANN_EVENT"), self.kind.name().to_string()),
+            (String::from("TIMEMANN_TAB"), tab.to_string()),
+            (String::from("TIMEMANN_LABEL"), self.label.clone()),
+        ];
+
+        let times = [("DURATION", self.duration), ("ELAPSED", self.elapsed), ("OVERSHOOT", self.overshoot)];
+        for (name, time) in times {
+            if let Some(time) = time {
+                env.push((format!("TIMEMANN_{name}"), format_duration(time)));
+                env.push((format!("TIMEMANN_{name}_MS"), time.as_millis().to_string()));
+            }
+        }
+        env
+    }
+}
+
+/// Default location of the hook log, under `$XDG_STATE_HOME/timemann`.
+pub fn default_log_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("timemann").join("hooks.log"))
+}
+
+/// Hooks started and not yet finished. They run on the tokio runtime, so a slow hook
+/// never holds up the interface.
+#[derive(Debug, Default)]
+pub struct Hooks {
+    running: JoinSet<()>,
+}
+
+impl Hooks {
+    /// Starts the hook configured for each event, if any.
+    pub fn fire(&mut self, config: &HooksConfig, events: Vec<(String, HookEvent)>) {
+        // forget hooks that are done, so the set does not grow for as long as the app runs
+        while self.running.try_join_next().is_some() {}
+
+        for (tab, event) in events {
+            let Some(command) = config.command(event.kind) else {
+                continue;
+            };
+            self.running.spawn(run(
+                command.to_string(),
+                event.env(&tab),
+                config.timeout,
+                config.log_path(),
+                event.kind,
+            ));
+        }
+    }
+
+    /// Waits for the running hooks to finish, which takes at most their timeout.
+    pub async fn wait(&mut self) {
+        while self.running.join_next().await.is_some() {}
+    }
+}
+
+/// Runs one hook through the shell and logs how it went along with its output.
+async fn run(command: String, env: Vec<(String, String)>, timeout: Duration, log: Option<PathBuf>, kind: HookKind) {
+    let mut shell = shell(&command);
+    shell
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // a hook that times out is dropped, and killed with it
+        .kill_on_drop(true);
+
+    let (result, output) = match shell.spawn() {
+        Ok(child) => match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Ok(Ok(output)) => {
+                let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+                text.push_str(&String::from_utf8_lossy(&output.stderr));
+                (output.status.to_string(), text)
+            }
+            Ok(Err(err)) => (format!("failed: {err}"), String::new()),
+            Err(_) => (format!("timed out after {}s and was killed", timeout.as_secs_f64()), String::new()),
+        },
+        Err(err) => (format!("failed to start: {err}"), String::new()),
+    };
+
+    if let Some(path) = log {
+        let mut entry = format!("{} {} `{}`: {}\n", Local::now().format("%F %T"), kind.name(), command, result);
+        for line in output.lines() {
+            entry.push_str("    ");
+            entry.push_str(line);
+            entry.push('\n');
+        }
+        // a hook log that cannot be written must not stop the hooks themselves
+        let _ = append(&path, &entry).await;
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(not(unix))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+async fn append(path: &Path, entry: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(entry.as_bytes()).await
+}
@@ -0,0 +1,428 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Ok, Result};
+use chrono::{DateTime, Utc};
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tui_big_text::BigText;
+
+use crate::clock::SharedClock;
+use crate::config::{Config, NotificationConfig};
+use crate::history::{Outcome, SessionRecord};
+use crate::hooks::{HookEvent, HookKind};
+use crate::tabs::{AppTab, TabStatus, TimerState};
+use crate::ui::{format_duration, next_change, setup_big_timer_text};
+use crate::workout::{load_workouts, Interval, Workout};
+use crate::{msg::Message, states::AppState, timer::{Timer, TimerSnapshot}};
+
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedIntervals {
+    workout: String,
+    timer: TimerSnapshot,
+    #[serde(default)]
+    started_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntervalTab {
+    state: AppState,
+    timer: Timer,
+    workouts: Vec<Workout>,
+    selected: usize,
+    /// Workout files that failed to load
+    errors: Vec<String>,
+    /// Interval shown on the last tick, to notice when the next one begins
+    current: Option<usize>,
+    notification: NotificationConfig,
+    /// Wall time at which the current workout was first started
+    started_at: Option<DateTime<Utc>>,
+    records: Vec<SessionRecord>,
+    hook_events: Vec<HookEvent>,
+}
+
+impl IntervalTab {
+
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            timer: Timer::with_clock(clock),
+            ..Self::default()
+        }
+    }
+
+    fn workout(&self) -> &Workout {
+        &self.workouts[self.selected]
+    }
+
+    fn is_idle(&self) -> bool {
+        self.state.is_stopped() && self.timer.elapsed().is_zero()
+    }
+
+    fn clear(&mut self) {
+        if self.state.is_stopped() {
+            self.record(Outcome::Cancelled);
+            self.timer.reset();
+            self.current = None;
+        }
+    }
+
+    fn toggle_start_pause(&mut self) {
+        let kind = if self.state.is_stopped() {
+            self.start();
+            HookKind::Start
+        } else {
+            self.stop();
+            HookKind::Pause
+        };
+        self.hook(kind);
+    }
+
+    fn start(&mut self) {
+        self.timer.start();
+        self.state = AppState::Running;
+        let now = self.timer.clock().utc();
+        self.started_at.get_or_insert(now);
+    }
+
+    /// Records the workout, if it was started, and forgets its start time.
+    fn record(&mut self, outcome: Outcome) {
+        let Some(started_at) = self.started_at.take() else {
+            return;
+        };
+        let workout = self.workout();
+        let record = SessionRecord::new(
+            "intervals",
+            workout.name.as_str(),
+            started_at,
+            self.timer.clock().utc(),
+            self.timer.elapsed().min(workout.total_duration()),
+            outcome,
+        );
+        self.records.push(record);
+    }
+
+    fn stop(&mut self) {
+        self.timer.pause();
+        self.state = AppState::Stopped;
+    }
+
+    fn skip(&mut self) {
+        if self.is_idle() {
+            return;
+        }
+
+        let next = self.current_interval().map_or(0, |(index, _)| index + 1);
+        self.timer.set_elapsed(self.workout().start_of(next));
+        self.check_interval();
+    }
+
+    fn select_workout(&mut self, forward: bool) {
+        if !self.is_idle() {
+            return;
+        }
+
+        let count = self.workouts.len();
+        self.selected = if forward {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+    }
+
+    fn current_interval(&self) -> Option<(usize, Duration)> {
+        self.workout().interval_at(self.timer.elapsed())
+    }
+
+    fn check_interval(&mut self) {
+        if self.is_idle() {
+            return;
+        }
+
+        match self.current_interval() {
+            Some((index, _)) if self.current != Some(index) => {
+                if self.current.is_some() {
+                    let interval = &self.workout().intervals[index];
+                    self.notify(&format!("{} ({})", interval.label, format_duration(interval.duration)));
+                    self.hook_events.push(HookEvent {
+                        duration: Some(interval.duration),
+                        elapsed: Some(self.timer.elapsed()),
+                        ..HookEvent::new(HookKind::PhaseChange, interval.label.as_str())
+                    });
+                }
+                self.current = Some(index);
+            }
+            Some(_) => (),
+            None => {
+                self.notify(&format!("{} complete!", self.workout().name));
+                let total = self.workout().total_duration();
+                self.hook_events.push(HookEvent {
+                    duration: Some(total),
+                    elapsed: Some(total),
+                    overshoot: Some(self.timer.elapsed().saturating_sub(total)),
+                    ..HookEvent::new(HookKind::Finish, self.workout().name.as_str())
+                });
+                self.record(Outcome::Completed);
+                self.stop();
+                self.clear();
+            }
+        }
+    }
+
+    /// An event about the whole workout, labelled with its name.
+    fn hook(&mut self, kind: HookKind) {
+        let total = self.workout().total_duration();
+        self.hook_events.push(HookEvent {
+            duration: Some(total),
+            elapsed: Some(self.timer.elapsed().min(total)),
+            ..HookEvent::new(kind, self.workout().name.as_str())
+        });
+    }
+
+    fn notify(&self, body: &str) {
+        self.notification.show(body);
+    }
+
+    fn load_workouts(&mut self, dir: Option<PathBuf>) {
+        let selected_name = self.workout().name.clone();
+
+        let (mut workouts, errors) = match dir {
+            Some(dir) => load_workouts(&dir),
+            None => (vec![], vec![]),
+        };
+        if workouts.is_empty() {
+            workouts.push(Workout::default());
+        }
+
+        // keep a running workout going even if its file was removed or changed
+        if !self.is_idle() {
+            if let Some(index) = workouts.iter().position(|w| w.name == selected_name) {
+                workouts[index] = self.workout().clone();
+            } else {
+                workouts.push(self.workout().clone());
+            }
+        }
+
+        self.selected = workouts.iter().position(|w| w.name == selected_name).unwrap_or(0);
+        self.workouts = workouts;
+        self.errors = errors;
+    }
+
+    /// Time left in the current interval, or the length of the first one before starting.
+    fn interval_remaining(&self) -> Duration {
+        match self.current_interval() {
+            Some((index, start)) => {
+                let end = start + self.workout().intervals[index].duration;
+                end.saturating_sub(self.timer.elapsed())
+            }
+            None => self.workout().intervals[0].duration,
+        }
+    }
+
+    fn timer_paragraph(&mut self, area: Rect) -> BigText<'_> {
+        setup_big_timer_text(
+            format_duration(self.interval_remaining()),
+            self.state,
+            area.width
+        )
+    }
+
+    fn title_paragraph(&self) -> Paragraph<'_> {
+        let workout = self.workout();
+        let mut spans = vec![Span::from(workout.name.as_str()).bold()];
+
+        if self.workouts.len() > 1 {
+            spans.push(format!(" ({}/{})", self.selected + 1, self.workouts.len()).dim());
+        }
+
+        let index = self.current_interval().map_or(0, |(index, _)| index);
+        let interval = &workout.intervals[index];
+        spans.push(format!("  ·  {}", interval.label).into());
+        if let Some((round, rounds)) = interval.round {
+            spans.push(format!("  ·  Round {round}/{rounds}, {} left", rounds - round).into());
+        }
+
+        Paragraph::new(Line::from(spans)).centered()
+    }
+
+    fn upcoming_paragraph(&self) -> Paragraph<'_> {
+        let workout = self.workout();
+        let next = self.current_interval().map_or(1, |(index, _)| index + 1);
+
+        let next_text = match workout.intervals.get(next) {
+            Some(Interval { label, duration, .. }) => format!("Next: {} {}", label, format_duration(*duration)),
+            None => String::from("Next: done"),
+        };
+        let total_left = workout.total_duration().saturating_sub(self.timer.elapsed());
+
+        Paragraph::new(Line::from(vec![
+            next_text.into(),
+            format!("  ·  Total left: {}", format_duration(total_left)).dim(),
+        ]))
+        .centered()
+    }
+
+    fn errors_paragraph(&self) -> Paragraph<'_> {
+        let lines: Vec<Line> = self.errors
+            .iter()
+            .map(|error| Line::from(error.as_str().red()))
+            .collect();
+
+        Paragraph::new(lines).centered()
+    }
+
+}
+
+pub fn layout(area: Rect) -> Vec<Rect> {
+    let layout = Layout::vertical(vec![
+        Constraint::Percentage(100), // INGORE
+        Constraint::Length(1), // title
+        Constraint::Min(8), // timer
+        Constraint::Length(1), // next step
+        Constraint::Length(1), // SPACE
+        Constraint::Length(3), // errors
+        Constraint::Percentage(100), // INGORE
+    ])
+    .split(area);
+
+    vec![layout[1], layout[2], layout[3], layout[5]]
+}
+
+impl AppTab for IntervalTab {
+    fn id(&self) -> &'static str {
+        "intervals"
+    }
+
+    fn title(&self) -> &'static str {
+        "Intervals"
+    }
+
+    fn icon(&self) -> &'static str {
+        "🔁"
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::ToggleStartPause => self.toggle_start_pause(),
+            Message::Clear => self.clear(),
+            Message::Skip => self.skip(),
+            Message::ScrollUp => self.select_workout(false),
+            Message::ScrollDown => self.select_workout(true),
+            _ => (),
+        }
+    }
+
+    fn tick(&mut self) {
+        self.check_interval();
+    }
+
+    fn redraw_in(&self) -> Option<Duration> {
+        self.state.is_running().then(|| next_change(self.interval_remaining(), true))
+    }
+
+    fn apply_config(&mut self, config: &Config) {
+        self.notification = config.notification.clone();
+        self.load_workouts(config.workouts_path());
+    }
+
+    fn render_paragraph(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
+        let area = layout(*area);
+        frame.render_widget(self.title_paragraph(), area[0]);
+        frame.render_widget(self.timer_paragraph(area[1]), area[1]);
+        frame.render_widget(self.upcoming_paragraph(), area[2]);
+
+        if self.is_idle() {
+            frame.render_widget(self.errors_paragraph(), area[3]);
+        }
+        Ok(())
+    }
+
+    fn status(&self) -> Option<TabStatus> {
+        let state = if self.is_idle() {
+            TimerState::Idle
+        } else if self.state.is_running() {
+            TimerState::Running
+        } else {
+            TimerState::Paused
+        };
+
+        let index = self.current_interval().map_or(0, |(index, _)| index);
+        Some(TabStatus {
+            state,
+            label: format!("{} · {}", self.workout().name, self.workout().intervals[index].label),
+            elapsed: Some(self.timer.elapsed()),
+            remaining: Some(self.interval_remaining()),
+        })
+    }
+
+    fn save_state(&self) -> Option<Value> {
+        let saved = SavedIntervals {
+            workout: self.workout().name.clone(),
+            timer: self.timer.snapshot(),
+            started_at: self.started_at,
+        };
+        serde_json::to_value(saved).ok()
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<Option<String>> {
+        let saved: SavedIntervals = serde_json::from_value(state)?;
+        self.selected = self.workouts
+            .iter()
+            .position(|workout| workout.name == saved.workout)
+            .ok_or_else(|| anyhow!("workout '{}' no longer exists", saved.workout))?;
+        self.timer = Timer::from_snapshot(saved.timer, self.timer.clock().clone());
+        self.state = if saved.timer.running { AppState::Running } else { AppState::Stopped };
+        self.current = self.current_interval().map(|(index, _)| index);
+        self.started_at = saved.started_at;
+        Ok(None)
+    }
+
+    fn take_records(&mut self) -> Vec<SessionRecord> {
+        std::mem::take(&mut self.records)
+    }
+
+    fn take_hook_events(&mut self) -> Vec<HookEvent> {
+        std::mem::take(&mut self.hook_events)
+    }
+
+    fn help_entries(&self) -> Vec<(Message, &'static str)> {
+        let mut entries = vec![(
+            Message::ToggleStartPause,
+            if self.state.is_stopped() { "Start" } else { "Pause" },
+        )];
+
+        if self.is_idle() && self.workouts.len() > 1 {
+            entries.push((Message::ScrollUp, "Choose Workout"));
+            entries.push((Message::ScrollDown, "Choose Workout"));
+        }
+
+        if !self.is_idle() {
+            entries.push((Message::Skip, "Next Step"));
+        }
+
+        if self.state.is_stopped() && !self.is_idle() {
+            entries.push((Message::Clear, "Reset"));
+        }
+
+        entries
+    }
+}
+
+impl Default for IntervalTab {
+    fn default() -> Self {
+        Self {
+            state: AppState::Stopped,
+            timer: Timer::new(),
+            workouts: vec![Workout::default()],
+            selected: 0,
+            errors: vec![],
+            current: None,
+            notification: NotificationConfig::default(),
+            started_at: None,
+            records: vec![],
+            hook_events: vec![],
+        }
+    }
+}
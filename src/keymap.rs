@@ -0,0 +1,572 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::msg::Message;
+use crate::tabs::tab_ids;
+
+
+/// Context whose bindings apply on every tab.
+pub const GLOBAL_CONTEXT: &str = "global";
+
+/// Context of a ringing alarm, which takes all input whatever tab is shown.
+pub const RINGING_CONTEXT: &str = "ringing";
+
+/// Bindings used when the config does not override them, as `(context, action, keys)`.
+const DEFAULT_BINDINGS: &[(&str, &str, &[&str])] = &[
+    (GLOBAL_CONTEXT, "change_tab", &["tab", "a"]),
+    (GLOBAL_CONTEXT, "quit", &["q", "ctrl+c"]),
+    (GLOBAL_CONTEXT, "suspend", &["ctrl+z"]),
+
+    ("stopwatch", "toggle_start_pause", &["enter"]),
+    ("stopwatch", "clear", &["c"]),
+    ("stopwatch", "lap", &["l"]),
+    ("stopwatch", "scroll_up", &["up"]),
+    ("stopwatch", "scroll_down", &["down"]),
+    ("stopwatch", "add_10s", &["]"]),
+    ("stopwatch", "subtract_10s", &["["]),
+    ("stopwatch", "add_1m", &["+", "="]),
+    ("stopwatch", "subtract_1m", &["-"]),
+    ("stopwatch", "add_5m", &["}"]),
+    ("stopwatch", "subtract_5m", &["{"]),
+
+    ("countdown", "toggle_start_pause", &["enter"]),
+    ("countdown", "clear", &["c"]),
+    ("countdown", "edit", &["e"]),
+    ("countdown", "backspace", &["backspace"]),
+    ("countdown", "delete", &["delete"]),
+    ("countdown", "cursor_left", &["left"]),
+    ("countdown", "cursor_right", &["right"]),
+    ("countdown", "add_10s", &["]"]),
+    ("countdown", "subtract_10s", &["["]),
+    ("countdown", "add_1m", &["+", "="]),
+    ("countdown", "subtract_1m", &["-"]),
+    ("countdown", "add_5m", &["}"]),
+    ("countdown", "subtract_5m", &["{"]),
+
+    ("timers", "toggle_start_pause", &["enter"]),
+    ("timers", "clear", &["c"]),
+    ("timers", "edit", &["e"]),
+    ("timers", "new", &["n"]),
+    ("timers", "rename", &["r"]),
+    ("timers", "remove", &["x"]),
+    ("timers", "cancel", &["esc"]),
+    ("timers", "scroll_up", &["up"]),
+    ("timers", "scroll_down", &["down"]),
+    ("timers", "backspace", &["backspace"]),
+    ("timers", "delete", &["delete"]),
+    ("timers", "cursor_left", &["left"]),
+    ("timers", "cursor_right", &["right"]),
+    ("timers", "add_10s", &["]"]),
+    ("timers", "subtract_10s", &["["]),
+    ("timers", "add_1m", &["+", "="]),
+    ("timers", "subtract_1m", &["-"]),
+    ("timers", "add_5m", &["}"]),
+    ("timers", "subtract_5m", &["{"]),
+
+    ("alarms", "toggle_start_pause", &["enter"]),
+    ("alarms", "new", &["n"]),
+    ("alarms", "edit", &["e"]),
+    ("alarms", "remove", &["x"]),
+    ("alarms", "cancel", &["esc"]),
+    ("alarms", "backspace", &["backspace"]),
+    ("alarms", "scroll_up", &["up"]),
+    ("alarms", "scroll_down", &["down"]),
+
+    (RINGING_CONTEXT, "dismiss", &["esc", "enter"]),
+    (RINGING_CONTEXT, "snooze", &["s", "space"]),
+
+    ("clocks", "toggle_view", &["v"]),
+
+    ("pomodoro", "toggle_start_pause", &["enter"]),
+    ("pomodoro", "clear", &["c"]),
+    ("pomodoro", "skip", &["n"]),
+
+    ("intervals", "toggle_start_pause", &["enter"]),
+    ("intervals", "clear", &["c"]),
+    ("intervals", "skip", &["n"]),
+    ("intervals", "scroll_up", &["up"]),
+    ("intervals", "scroll_down", &["down"]),
+
+    ("history", "scroll_up", &["up"]),
+    ("history", "scroll_down", &["down"]),
+    ("history", "delete", &["delete", "d"]),
+    ("history", "filter", &["f"]),
+];
+
+/// Messages that can be bound to keys, by the name used in the config file.
+const ACTIONS: &[(&str, Message)] = &[
+    ("toggle_start_pause", Message::ToggleStartPause),
+    ("change_tab", Message::ChangeTab),
+    ("clear", Message::Clear),
+    ("quit", Message::Quit),
+    ("suspend", Message::Suspend),
+    ("edit", Message::Edit),
+    ("lap", Message::Lap),
+    ("scroll_up", Message::ScrollUp),
+    ("scroll_down", Message::ScrollDown),
+    ("backspace", Message::Backspace),
+    ("delete", Message::Delete),
+    ("cursor_left", Message::CursorLeft),
+    ("cursor_right", Message::CursorRight),
+    ("skip", Message::Skip),
+    ("filter", Message::Filter),
+    ("new", Message::New),
+    ("rename", Message::Rename),
+    ("remove", Message::Remove),
+    ("cancel", Message::Cancel),
+    ("dismiss", Message::Dismiss),
+    ("snooze", Message::Snooze),
+    ("toggle_view", Message::ToggleView),
+    ("add_10s", Message::Adjust(10)),
+    ("subtract_10s", Message::Adjust(-10)),
+    ("add_1m", Message::Adjust(60)),
+    ("subtract_1m", Message::Adjust(-60)),
+    ("add_5m", Message::Adjust(300)),
+    ("subtract_5m", Message::Adjust(-300)),
+];
+
+/// Which key presses the current tab wants as typed text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TextInput {
+    #[default]
+    Off,
+    /// Printable keys that are not bound, e.g. the digits and units of a duration
+    Unbound,
+    /// Every printable key without modifiers, bound or not, e.g. while typing a label
+    All,
+}
+
+/// The `[keys.<context>]` config sections: action name to one or more key sequences.
+pub type KeysConfig = BTreeMap<String, BTreeMap<String, KeyList>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    fn as_slice(&self) -> &[String] {
+        match self {
+            KeyList::One(key) => std::slice::from_ref(key),
+            KeyList::Many(keys) => keys,
+        }
+    }
+}
+
+/// A single key press together with its modifiers, e.g. `ctrl+x`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyCombo {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        match code {
+            // the character already tells whether shift was held
+            KeyCode::Char(_) => Self { code, modifiers: modifiers - KeyModifiers::SHIFT },
+            KeyCode::BackTab => Self { code: KeyCode::Tab, modifiers: modifiers | KeyModifiers::SHIFT },
+            _ => Self { code, modifiers },
+        }
+    }
+}
+
+impl From<KeyEvent> for KeyCombo {
+    fn from(key: KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        // a trailing `+` is the plus key itself, as in `+` or `ctrl++`
+        let (modifier_part, key_part) = match input.strip_suffix('+') {
+            Some(rest) if rest.is_empty() || rest.ends_with('+') => (rest.trim_end_matches('+'), "+"),
+            _ => match input.rsplit_once('+') {
+                Some((modifiers, key)) => (modifiers, key),
+                None => ("", input),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in modifier_part.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier '{modifier}' in '{input}'"),
+            };
+        }
+
+        let mut chars = key_part.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::Char(c.to_ascii_uppercase()),
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key_part.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "esc" | "escape" => KeyCode::Esc,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => bail!("unknown key '{key_part}' in '{input}'"),
+                },
+            },
+        };
+
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+
+        match self.code {
+            // lowercase letters are shown in capitals, like the labels on a keyboard
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if c.is_ascii_uppercase() => write!(f, "Shift+{c}"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => {
+                if self.modifiers.contains(KeyModifiers::SHIFT) {
+                    write!(f, "Shift+")?;
+                }
+                write!(f, "{code:?}")
+            }
+        }
+    }
+}
+
+type KeySequence = Vec<KeyCombo>;
+
+fn parse_sequence(input: &str) -> Result<KeySequence> {
+    let sequence = input
+        .split_whitespace()
+        .map(KeyCombo::from_str)
+        .collect::<Result<KeySequence>>()?;
+    if sequence.is_empty() {
+        bail!("empty key binding");
+    }
+    Ok(sequence)
+}
+
+fn format_sequence(sequence: &[KeyCombo]) -> String {
+    sequence.iter().map(KeyCombo::to_string).collect::<Vec<_>>().join(" ")
+}
+
+pub fn parse_action(name: &str) -> Result<Message> {
+    ACTIONS
+        .iter()
+        .find(|(action, _)| *action == name)
+        .map(|(_, message)| *message)
+        .ok_or_else(|| anyhow!("unknown action '{name}'"))
+}
+
+fn action_name(message: Message) -> &'static str {
+    ACTIONS
+        .iter()
+        .find(|(_, action)| *action == message)
+        .map_or("?", |(name, _)| name)
+}
+
+enum Lookup {
+    Exact(Message),
+    Prefix,
+    Unbound,
+}
+
+/// Turns key presses into `Message`s, using the bindings of the current tab's context
+/// before the global ones.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    contexts: BTreeMap<String, Vec<(KeySequence, Message)>>,
+    pending: KeySequence,
+}
+
+impl Keymap {
+    /// Builds the keymap from the defaults, overridden per action by `config`.
+    pub fn new(config: &KeysConfig) -> Result<Self> {
+        // one context per tab, plus the global and the ringing one
+        let mut actions: BTreeMap<String, BTreeMap<String, Vec<String>>> = [GLOBAL_CONTEXT, RINGING_CONTEXT]
+            .into_iter()
+            .chain(tab_ids())
+            .map(|context| (context.to_string(), BTreeMap::new()))
+            .collect();
+        for (context, action, keys) in DEFAULT_BINDINGS {
+            actions
+                .entry(context.to_string())
+                .or_default()
+                .insert(action.to_string(), keys.iter().map(|k| k.to_string()).collect());
+        }
+
+        for (context, bindings) in config {
+            let context_actions = actions
+                .get_mut(context)
+                .ok_or_else(|| anyhow!("unknown key context [keys.{context}]"))?;
+            for (action, keys) in bindings {
+                context_actions.insert(action.clone(), keys.as_slice().to_vec());
+            }
+        }
+
+        let mut contexts = BTreeMap::new();
+        for (context, context_actions) in &actions {
+            let mut bindings: Vec<(KeySequence, Message)> = vec![];
+            for (action, keys) in context_actions {
+                let message = parse_action(action).with_context(|| format!("in [keys.{context}]"))?;
+                for key in keys {
+                    let sequence = parse_sequence(key).with_context(|| format!("in [keys.{context}]"))?;
+                    if let Some((_, other)) = bindings.iter().find(|(existing, _)| *existing == sequence) {
+                        if *other != message {
+                            bail!(
+                                "key '{}' in [keys.{}] is bound to both '{}' and '{}'",
+                                key, context, action_name(*other), action
+                            );
+                        }
+                        continue;
+                    }
+                    bindings.push((sequence, message));
+                }
+            }
+            contexts.insert(context.clone(), bindings);
+        }
+
+        let keymap = Self { contexts, pending: vec![] };
+        keymap.check_prefix_conflicts()?;
+        Ok(keymap)
+    }
+
+    /// Rejects chords that could never be typed because a shorter binding fires first,
+    /// taking into account the global bindings that are active in every context.
+    fn check_prefix_conflicts(&self) -> Result<()> {
+        let global = &self.contexts[GLOBAL_CONTEXT];
+        for (context, bindings) in &self.contexts {
+            let active = bindings.iter().chain(global.iter().filter(|(sequence, _)| {
+                !bindings.iter().any(|(own, _)| own == sequence)
+            }));
+
+            for (short, short_action) in active.clone() {
+                for (long, long_action) in active.clone() {
+                    if long.len() > short.len() && long.starts_with(short) {
+                        bail!(
+                            "key '{}' ('{}') hides '{}' ('{}') in [keys.{}]",
+                            format_sequence(short), action_name(*short_action),
+                            format_sequence(long), action_name(*long_action),
+                            context
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolves a key press in `context`, turning printable keys into `Message::Input` as
+    /// `text_input` asks. Returns `None` while a chord is still being typed.
+    pub fn resolve(&mut self, context: &str, key: KeyEvent, text_input: TextInput) -> Option<Message> {
+        let combo = KeyCombo::from(key);
+        let typed = match combo.code {
+            KeyCode::Char(c) if combo.modifiers.is_empty() => Some(Message::Input(c)),
+            _ => None,
+        };
+        if text_input == TextInput::All && self.pending.is_empty() && typed.is_some() {
+            return typed;
+        }
+        self.pending.push(combo);
+
+        match self.lookup(context, &self.pending) {
+            Lookup::Exact(message) => {
+                self.pending.clear();
+                Some(message)
+            }
+            Lookup::Prefix => None,
+            Lookup::Unbound => {
+                let was_chord = self.pending.len() > 1;
+                self.pending.clear();
+                if was_chord {
+                    return self.resolve(context, key, text_input);
+                }
+
+                typed.filter(|_| text_input != TextInput::Off)
+            }
+        }
+    }
+
+    fn lookup(&self, context: &str, sequence: &[KeyCombo]) -> Lookup {
+        for context in [context, GLOBAL_CONTEXT] {
+            let Some(bindings) = self.contexts.get(context) else {
+                continue;
+            };
+            if let Some((_, message)) = bindings.iter().find(|(bound, _)| bound == sequence) {
+                return Lookup::Exact(*message);
+            }
+            if bindings.iter().any(|(bound, _)| bound.starts_with(sequence)) {
+                return Lookup::Prefix;
+            }
+        }
+        Lookup::Unbound
+    }
+
+    /// The first key bound to `action` in `context` or globally, formatted for the help bar.
+    /// Keys that would be typed as text under `text_input` are skipped.
+    pub fn key_for(&self, context: &str, action: Message, text_input: TextInput) -> Option<String> {
+        let typed = |sequence: &KeySequence| {
+            text_input == TextInput::All
+                && matches!(sequence[0].code, KeyCode::Char(_))
+                && sequence[0].modifiers.is_empty()
+        };
+
+        [context, GLOBAL_CONTEXT]
+            .iter()
+            .filter_map(|context| self.contexts.get(*context))
+            .flat_map(|bindings| bindings.iter())
+            .find(|(sequence, message)| *message == action && !typed(sequence))
+            .map(|(sequence, _)| format_sequence(sequence))
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&KeysConfig::new()).expect("default key bindings are valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap(config: &str) -> Result<Keymap> {
+        Keymap::new(&toml::from_str(config).unwrap())
+    }
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn parses_keys_with_modifiers() {
+        for (input, code, modifiers) in [
+            ("q", KeyCode::Char('q'), KeyModifiers::NONE),
+            ("ctrl+c", KeyCode::Char('c'), KeyModifiers::CONTROL),
+            ("Control+Alt+x", KeyCode::Char('x'), KeyModifiers::CONTROL | KeyModifiers::ALT),
+            ("+", KeyCode::Char('+'), KeyModifiers::NONE),
+            ("ctrl++", KeyCode::Char('+'), KeyModifiers::CONTROL),
+            ("ctrl+shift++", KeyCode::Char('+'), KeyModifiers::CONTROL),
+            ("shift+a", KeyCode::Char('A'), KeyModifiers::NONE),
+            ("A", KeyCode::Char('A'), KeyModifiers::NONE),
+            ("space", KeyCode::Char(' '), KeyModifiers::NONE),
+            ("Enter", KeyCode::Enter, KeyModifiers::NONE),
+            ("shift+tab", KeyCode::Tab, KeyModifiers::SHIFT),
+            ("alt+pageup", KeyCode::PageUp, KeyModifiers::ALT),
+            ("f12", KeyCode::F(12), KeyModifiers::NONE),
+        ] {
+            assert_eq!(KeyCombo::from_str(input).unwrap(), KeyCombo::new(code, modifiers), "{input}");
+        }
+    }
+
+    #[test]
+    fn parsed_keys_match_key_presses() {
+        for (input, code, modifiers) in [
+            ("shift+a", KeyCode::Char('A'), KeyModifiers::SHIFT),
+            ("A", KeyCode::Char('A'), KeyModifiers::SHIFT),
+            ("a", KeyCode::Char('a'), KeyModifiers::NONE),
+            ("shift+tab", KeyCode::BackTab, KeyModifiers::SHIFT),
+            ("ctrl+z", KeyCode::Char('z'), KeyModifiers::CONTROL),
+        ] {
+            let pressed = KeyCombo::from(KeyEvent::new(code, modifiers));
+            assert_eq!(KeyCombo::from_str(input).unwrap(), pressed, "{input}");
+        }
+        assert_ne!(KeyCombo::from_str("shift+a").unwrap(), KeyCombo::from_str("a").unwrap());
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_modifiers() {
+        for (input, expected) in [
+            ("enterr", "unknown key 'enterr' in 'enterr'"),
+            ("ctrl+foo", "unknown key 'foo' in 'ctrl+foo'"),
+            ("f13", "unknown key 'f13' in 'f13'"),
+            ("ctrl+", "unknown key '' in 'ctrl+'"),
+            ("hyper+a", "unknown modifier 'hyper' in 'hyper+a'"),
+        ] {
+            assert_eq!(KeyCombo::from_str(input).unwrap_err().to_string(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn resolves_chords() {
+        let mut keymap = keymap("[stopwatch]\nlap = 'g l'").unwrap();
+        for (keys, text_input, expected) in [
+            ("gl", TextInput::Off, Some(Message::Lap)),
+            ("l", TextInput::Off, None),
+            // a key that does not finish the chord is resolved on its own
+            ("gc", TextInput::Off, Some(Message::Clear)),
+            ("gx", TextInput::Off, None),
+            ("gx", TextInput::Unbound, Some(Message::Input('x'))),
+            ("g", TextInput::All, Some(Message::Input('g'))),
+            ("q", TextInput::Off, Some(Message::Quit)),
+        ] {
+            let mut resolved: Vec<_> = keys.chars().map(|c| keymap.resolve("stopwatch", key(c), text_input)).collect();
+            let last = resolved.pop().unwrap();
+            assert!(resolved.iter().all(Option::is_none), "{keys}");
+            assert_eq!(last, expected, "{keys} with {text_input:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_chords_hidden_by_shorter_keys() {
+        for (config, expected) in [
+            (
+                "[stopwatch]\nclear = 'g'\nlap = 'g l'",
+                "key 'G' ('clear') hides 'G L' ('lap') in [keys.stopwatch]",
+            ),
+            (
+                "[stopwatch]\nlap = 'q l'",
+                "key 'Q' ('quit') hides 'Q L' ('lap') in [keys.stopwatch]",
+            ),
+        ] {
+            assert_eq!(keymap(config).unwrap_err().to_string(), expected, "{config}");
+        }
+
+        // without the global binding the chord can be typed
+        keymap("[global]\nquit = 'ctrl+c'\n[stopwatch]\nlap = 'q l'").unwrap();
+    }
+
+    #[test]
+    fn rejects_keys_bound_to_two_actions() {
+        for (config, expected) in [
+            ("[stopwatch]\nclear = 'l'", "key 'l' in [keys.stopwatch] is bound to both 'clear' and 'lap'"),
+            ("[global]\nquit = ['q', 'tab']", "key 'tab' in [keys.global] is bound to both 'change_tab' and 'quit'"),
+        ] {
+            assert_eq!(keymap(config).unwrap_err().to_string(), expected, "{config}");
+        }
+
+        // the same key twice for one action, or in another context, is fine
+        keymap("[stopwatch]\nlap = ['l', 'l']\n[pomodoro]\nlap = 'l'").unwrap();
+    }
+}
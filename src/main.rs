@@ -1,27 +1,71 @@
+use std::process::ExitCode;
+
 use anyhow::Result;
+use clap::Parser;
 
+mod cli;
+mod clock;
+mod config;
+mod duration;
+mod target;
 mod ui;
 mod tui;
 mod eve;
+mod keymap;
 mod msg;
 mod fps;
 mod app;
+mod headless;
+mod control;
+mod status;
+mod signals;
 mod splash;
 mod tabs;
 mod colors;
 mod states;
+mod session;
+mod watcher;
+mod history;
+mod hooks;
+mod alarm;
 
 mod timer;
+mod workout;
 
 mod stopwatch_tab;
 mod countdown_tab;
+mod timers_tab;
+mod alarm_tab;
+mod world_clock_tab;
+mod pomodoro_tab;
+mod interval_tab;
+mod history_tab;
 mod about_tab;
 
 use app::App;
+use cli::{Cli, Command};
+use control::Request;
+use status::StatusOptions;
 
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let mut app = App::default();
-    app.run().await
+async fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
+    if let Some(Command::Ctl { action, value, tab }) = cli.command.clone() {
+        let request = Request::from_args(action, value, tab)?;
+        return control::run_ctl(&cli.socket_path(), request).await;
+    }
+
+    if let Some(Command::Status { format, template, tab, watch, interval }) = cli.command.clone() {
+        let options = StatusOptions { format, template, tab, watch, interval };
+        return status::run(&cli.socket_path(), options).await;
+    }
+
+    if let Some(output) = cli.output() {
+        return headless::run(&cli, output).await;
+    }
+
+    let mut app = App::new(&cli)?;
+    app.run().await?;
+    Ok(ExitCode::SUCCESS)
 }
\ No newline at end of file
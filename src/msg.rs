@@ -1,13 +1,56 @@
+use std::time::Duration;
+
+use crossterm::event::KeyEvent;
+
+use crate::target::Target;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Message {
     // COUNTDOWN Tab
-    SetNumber(u8),
+    Input(char),
+    Backspace,
+    Delete,
+    CursorLeft,
+    CursorRight,
+    SetDuration(Duration),
+    SetTarget(Target),
     Edit,
 
+    // TIMERS Tab
+    New,
+    Rename,
+    Remove,
+    Cancel,
+
+    // STOPWATCH Tab
+    Lap,
+    ScrollUp,
+    ScrollDown,
+
+    // POMODORO Tab
+    Skip,
+
+    // ALARMS Tab
+    Dismiss,
+    Snooze,
+
+    // CLOCKS Tab
+    ToggleView,
+
+    // HISTORY Tab
+    Filter,
+
     // COMMON
+    Key(KeyEvent),
     ToggleStartPause,
     ChangeTab,
     Clear,
+    /// Seconds to add to the running timer, negative to take time away
+    Adjust(i64),
     Tick,
+    /// Hand the terminal back to the shell until continued with `fg`
+    Suspend,
+    /// The terminal changed size, so everything is drawn anew
+    Resize,
     Quit,
-}
\ No newline at end of file
+}
@@ -0,0 +1,377 @@
+use std::time::Duration;
+
+use anyhow::{Ok, Result};
+use chrono::{DateTime, Utc};
+use ratatui::prelude::*;
+use ratatui::widgets::{Gauge, Paragraph};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tui_big_text::BigText;
+
+use crate::clock::SharedClock;
+use crate::colors::theme;
+use crate::config::{Config, NotificationConfig, PomodoroConfig};
+use crate::history::{Outcome, SessionRecord};
+use crate::hooks::{HookEvent, HookKind};
+use crate::tabs::{AppTab, TabStatus, TimerState};
+use crate::ui::{format_duration, next_change, setup_big_timer_text};
+use crate::{msg::Message, states::AppState, timer::{Timer, TimerSnapshot}};
+
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    #[default]
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    fn title(&self) -> &'static str {
+        match self {
+            Phase::Work => "Work",
+            Phase::ShortBreak => "Short Break",
+            Phase::LongBreak => "Long Break",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedPomodoro {
+    phase: Phase,
+    completed: u32,
+    timer: TimerSnapshot,
+    #[serde(default)]
+    started_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PomodoroTab {
+    state: AppState,
+    timer: Timer,
+    phase: Phase,
+    /// Work sessions finished since the last long break
+    completed: u32,
+    settings: PomodoroConfig,
+    notification: NotificationConfig,
+    /// Wall time at which the current phase was first started
+    started_at: Option<DateTime<Utc>>,
+    records: Vec<SessionRecord>,
+    hook_events: Vec<HookEvent>,
+}
+
+impl PomodoroTab {
+
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            timer: Timer::with_clock(clock),
+            ..Self::default()
+        }
+    }
+
+    fn clear(&mut self) {
+        if self.state.is_stopped() {
+            self.record(Outcome::Cancelled);
+            self.timer.reset();
+            self.phase = Phase::Work;
+            self.completed = 0;
+        }
+    }
+
+    fn toggle_start_pause(&mut self) {
+        let kind = if self.state.is_stopped() {
+            self.start();
+            HookKind::Start
+        } else {
+            self.stop();
+            HookKind::Pause
+        };
+        self.hook(kind);
+    }
+
+    fn start(&mut self) {
+        self.timer.start();
+        self.state = AppState::Running;
+        let now = self.timer.clock().utc();
+        self.started_at.get_or_insert(now);
+    }
+
+    fn stop(&mut self) {
+        self.timer.pause();
+        self.state = AppState::Stopped;
+    }
+
+    fn phase_duration(&self) -> Duration {
+        match self.phase {
+            Phase::Work => self.settings.work,
+            Phase::ShortBreak => self.settings.short_break,
+            Phase::LongBreak => self.settings.long_break,
+        }
+    }
+
+    fn remaining(&self) -> Duration {
+        self.phase_duration().saturating_sub(self.timer.elapsed())
+    }
+
+    /// Records the current phase, if it was started, and forgets its start time.
+    fn record(&mut self, outcome: Outcome) {
+        let Some(started_at) = self.started_at.take() else {
+            return;
+        };
+        self.records.push(SessionRecord::new(
+            "pomodoro",
+            self.phase.title(),
+            started_at,
+            self.timer.clock().utc(),
+            self.timer.elapsed().min(self.phase_duration()),
+            outcome,
+        ));
+    }
+
+    /// Moves on to the next phase, starting it right away when `auto_start` is set
+    /// or when the skipped phase was already running.
+    fn next_phase(&mut self, keep_running: bool) {
+        let outcome = if self.timer.elapsed() >= self.phase_duration() {
+            Outcome::Completed
+        } else {
+            Outcome::Cancelled
+        };
+        if outcome.is_completed() {
+            self.hook_events.push(HookEvent {
+                overshoot: Some(self.timer.elapsed().saturating_sub(self.phase_duration())),
+                ..self.hook_event(HookKind::Finish)
+            });
+        }
+        self.record(outcome);
+
+        let finished = self.phase;
+        self.phase = match finished {
+            Phase::Work => {
+                self.completed += 1;
+                if self.completed >= self.settings.cycles {
+                    Phase::LongBreak
+                } else {
+                    Phase::ShortBreak
+                }
+            }
+            Phase::LongBreak => {
+                self.completed = 0;
+                Phase::Work
+            }
+            Phase::ShortBreak => Phase::Work,
+        };
+
+        self.timer.reset();
+        if keep_running {
+            self.start();
+        } else {
+            self.state = AppState::Stopped;
+        }
+        self.hook(HookKind::PhaseChange);
+
+        self.notification.show(&format!(
+            "{} finished, {} ({}) is up next",
+            finished.title(),
+            self.phase.title(),
+            format_duration(self.phase_duration())
+        ));
+    }
+
+    /// An event about the current phase, labelled with its title.
+    fn hook_event(&self, kind: HookKind) -> HookEvent {
+        HookEvent {
+            duration: Some(self.phase_duration()),
+            elapsed: Some(self.timer.elapsed().min(self.phase_duration())),
+            ..HookEvent::new(kind, self.phase.title())
+        }
+    }
+
+    fn hook(&mut self, kind: HookKind) {
+        let event = self.hook_event(kind);
+        self.hook_events.push(event);
+    }
+
+    fn skip(&mut self) {
+        self.next_phase(self.state.is_running());
+    }
+
+    fn check_phase(&mut self) {
+        if self.state.is_running() && self.timer.elapsed() >= self.phase_duration() {
+            self.next_phase(self.settings.auto_start);
+        }
+    }
+
+    fn timer_paragraph(&mut self, area: Rect) -> BigText<'_> {
+        setup_big_timer_text(
+            format_duration(self.remaining()),
+            self.state,
+            area.width
+        )
+    }
+
+    fn phase_paragraph(&self) -> Paragraph<'_> {
+        let waiting = if self.state.is_stopped() && self.timer.elapsed().is_zero() {
+            " - press start when ready"
+        } else {
+            ""
+        };
+
+        Paragraph::new(format!("{}{}", self.phase.title(), waiting))
+            .bold()
+            .centered()
+    }
+
+    fn progress_gauge(&self) -> Gauge<'_> {
+        let ratio = self.timer.elapsed().as_secs_f64() / self.phase_duration().as_secs_f64();
+        Gauge::default()
+            .ratio(ratio.clamp(0.0, 1.0))
+            .label("")
+            .gauge_style(Style::new().fg(theme().running).bg(theme().main_background))
+    }
+
+    /// One dot per work session until the long break, filled in for the finished ones.
+    fn cycles_paragraph(&self) -> Paragraph<'_> {
+        let dots: Vec<Span> = (0..self.settings.cycles)
+            .map(|cycle| {
+                let in_progress = cycle == self.completed && self.phase == Phase::Work;
+                if cycle < self.completed || self.phase == Phase::LongBreak {
+                    "● ".into()
+                } else if in_progress {
+                    "◐ ".into()
+                } else {
+                    "○ ".dim()
+                }
+            })
+            .collect();
+
+        Paragraph::new(Line::from(dots)).centered()
+    }
+
+}
+
+pub fn layout(area: Rect) -> Vec<Rect> {
+    let layout = Layout::vertical(vec![
+        Constraint::Percentage(100), // INGORE
+        Constraint::Length(1), // phase
+        Constraint::Min(8), // timer
+        Constraint::Length(1), // progress
+        Constraint::Length(1), // SPACE
+        Constraint::Length(1), // cycles
+        Constraint::Percentage(100), // INGORE
+    ])
+    .split(area);
+
+    let gauge = Layout::horizontal(vec![
+        Constraint::Percentage(20),
+        Constraint::Percentage(60),
+        Constraint::Percentage(20),
+    ])
+    .split(layout[3]);
+
+    vec![layout[1], layout[2], gauge[1], layout[5]]
+}
+
+impl AppTab for PomodoroTab {
+    fn id(&self) -> &'static str {
+        "pomodoro"
+    }
+
+    fn title(&self) -> &'static str {
+        "Pomodoro"
+    }
+
+    fn icon(&self) -> &'static str {
+        "🍅"
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::ToggleStartPause => self.toggle_start_pause(),
+            Message::Clear => self.clear(),
+            Message::Skip => self.skip(),
+            _ => (),
+        }
+    }
+
+    fn tick(&mut self) {
+        self.check_phase();
+    }
+
+    fn redraw_in(&self) -> Option<Duration> {
+        self.state.is_running().then(|| next_change(self.remaining(), true))
+    }
+
+    fn apply_config(&mut self, config: &Config) {
+        self.settings = config.pomodoro.clone();
+        self.notification = config.notification.clone();
+    }
+
+    fn render_paragraph(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
+        let area = layout(*area);
+        frame.render_widget(self.phase_paragraph(), area[0]);
+        frame.render_widget(self.timer_paragraph(area[1]), area[1]);
+        frame.render_widget(self.progress_gauge(), area[2]);
+        frame.render_widget(self.cycles_paragraph(), area[3]);
+        Ok(())
+    }
+
+    fn status(&self) -> Option<TabStatus> {
+        let state = if self.state.is_running() {
+            TimerState::Running
+        } else if self.timer.elapsed().is_zero() {
+            TimerState::Idle
+        } else {
+            TimerState::Paused
+        };
+
+        Some(TabStatus {
+            state,
+            label: self.phase.title().to_string(),
+            elapsed: Some(self.timer.elapsed()),
+            remaining: Some(self.remaining()),
+        })
+    }
+
+    fn save_state(&self) -> Option<Value> {
+        let saved = SavedPomodoro {
+            phase: self.phase,
+            completed: self.completed,
+            timer: self.timer.snapshot(),
+            started_at: self.started_at,
+        };
+        serde_json::to_value(saved).ok()
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<Option<String>> {
+        let saved: SavedPomodoro = serde_json::from_value(state)?;
+        self.phase = saved.phase;
+        self.completed = saved.completed;
+        self.timer = Timer::from_snapshot(saved.timer, self.timer.clock().clone());
+        self.state = if saved.timer.running { AppState::Running } else { AppState::Stopped };
+        self.started_at = saved.started_at;
+        Ok(None)
+    }
+
+    fn take_records(&mut self) -> Vec<SessionRecord> {
+        std::mem::take(&mut self.records)
+    }
+
+    fn take_hook_events(&mut self) -> Vec<HookEvent> {
+        std::mem::take(&mut self.hook_events)
+    }
+
+    fn help_entries(&self) -> Vec<(Message, &'static str)> {
+        let mut entries = vec![(
+            Message::ToggleStartPause,
+            if self.state.is_stopped() { "Start" } else { "Pause" },
+        )];
+
+        entries.push((Message::Skip, "Next Phase"));
+
+        if self.state.is_stopped() && (self.completed > 0 || !self.timer.elapsed().is_zero()) {
+            entries.push((Message::Clear, "Reset"));
+        }
+
+        entries
+    }
+}
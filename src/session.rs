@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+
+/// Timer state of every tab, saved on quit and periodically while running so it can be
+/// resumed on the next launch.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Saved state per tab, keyed by `AppTab::id`
+    pub tabs: BTreeMap<String, Value>,
+}
+
+impl Session {
+    /// Default location of the session file, under `$XDG_STATE_HOME/timemann`.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("timemann").join("session.json"))
+    }
+
+    /// Reads the session at `path`, returning an empty session when there is none yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read session file {}", path.display()))
+            }
+        };
+
+        serde_json::from_str(&text).with_context(|| format!("invalid session file {}", path.display()))
+    }
+
+    /// Writes the session next to `path` first and then moves it in place, so a crash
+    /// while saving never leaves a half-written file behind.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+
+        let temp_path = path.with_extension("json.tmp");
+        let text = serde_json::to_string_pretty(self)?;
+        fs::write(&temp_path, text).with_context(|| format!("failed to write {}", temp_path.display()))?;
+        fs::rename(&temp_path, path).with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+
+
+/// What a signal asks of the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGINT, SIGTERM or SIGHUP: save the timers and quit
+    Quit,
+    /// SIGTSTP: hand the terminal back and stop until continued
+    Suspend,
+}
+
+/// Signals the app handles instead of being killed or stopped right away. In raw mode
+/// Ctrl+C and Ctrl+Z arrive as keys, so these come from `kill`, a closing terminal or a
+/// shutting down system.
+#[cfg(unix)]
+pub struct Signals {
+    interrupt: tokio::signal::unix::Signal,
+    terminate: tokio::signal::unix::Signal,
+    hangup: tokio::signal::unix::Signal,
+    suspend: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    pub fn new() -> Result<Self> {
+        use anyhow::Context;
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let listen = |kind: SignalKind| signal(kind).context("failed to listen for signals");
+        Ok(Self {
+            interrupt: listen(SignalKind::interrupt())?,
+            terminate: listen(SignalKind::terminate())?,
+            hangup: listen(SignalKind::hangup())?,
+            suspend: listen(SignalKind::from_raw(libc::SIGTSTP))?,
+        })
+    }
+
+    pub async fn next(&mut self) -> Signal {
+        tokio::select! {
+            _ = self.interrupt.recv() => Signal::Quit,
+            _ = self.terminate.recv() => Signal::Quit,
+            _ = self.hangup.recv() => Signal::Quit,
+            _ = self.suspend.recv() => Signal::Suspend,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    pub async fn next(&mut self) -> Signal {
+        let _ = tokio::signal::ctrl_c().await;
+        Signal::Quit
+    }
+}
@@ -1,7 +1,8 @@
+use serde::{Deserialize, Serialize};
 use strum::EnumIs;
 
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIs)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIs, Serialize, Deserialize)]
 pub enum AppState {
     #[default]
     Stopped,
@@ -0,0 +1,312 @@
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde_json::json;
+
+use crate::control::{self, Request};
+use crate::tabs::{TabStatus, TimerState};
+use crate::ui::{format_duration, set_time_format, TimeFormat};
+
+
+/// How `timemann status` prints the timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatusFormat {
+    /// The template as is, one line per update
+    Plain,
+    /// JSON for a waybar custom module with `return-type: json`
+    Waybar,
+    /// A block of the i3bar protocol, also read by swaybar and i3blocks
+    I3bar,
+    /// The template with tmux colors, for `status-right`
+    Tmux,
+}
+
+/// Options of `timemann status`.
+#[derive(Debug, Clone)]
+pub struct StatusOptions {
+    pub format: StatusFormat,
+    pub template: String,
+    pub tab: Option<String>,
+    pub watch: bool,
+    pub interval: Duration,
+}
+
+/// The tab worth showing and its status.
+type Shown = (String, TabStatus);
+
+/// Prints the timer of the running app once, or keeps printing it with `--watch`.
+/// Prints an empty status while no app is running, so a bar simply hides it.
+pub async fn run(socket: &Path, options: StatusOptions) -> Result<ExitCode> {
+    set_time_format(TimeFormat::Seconds);
+
+    if !options.watch {
+        let shown = query(socket, options.tab.as_deref()).await?;
+        let line = match options.format {
+            StatusFormat::I3bar => serde_json::to_string(&[i3bar_block(&options.template, shown.as_ref())])?,
+            _ => format_line(&options, shown.as_ref()),
+        };
+        println!("{line}");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let mut stdout = io::stdout().lock();
+    if options.format == StatusFormat::I3bar {
+        writeln!(stdout, "{}", json!({ "version": 1 }))?;
+        writeln!(stdout, "[")?;
+    }
+
+    let mut interval = tokio::time::interval(options.interval);
+    loop {
+        interval.tick().await;
+        let shown = query(socket, options.tab.as_deref()).await?;
+        let line = match options.format {
+            // an endless array of status lines, each but the first preceded by a comma
+            StatusFormat::I3bar => format!("[{}],", i3bar_block(&options.template, shown.as_ref())),
+            _ => format_line(&options, shown.as_ref()),
+        };
+        writeln!(stdout, "{line}").context("failed to write to stdout")?;
+        stdout.flush()?;
+    }
+}
+
+/// Asks the app for the status and picks the tab to show. `None` when no app is running
+/// or no timer is active.
+async fn query(socket: &Path, tab: Option<&str>) -> Result<Option<Shown>> {
+    let request = Request {
+        action: String::from("status"),
+        tab: tab.map(String::from),
+        duration: None,
+        until: None,
+    };
+
+    let Ok(response) = control::send(socket, &request).await else {
+        return Ok(None);
+    };
+    if let Some(error) = response.error {
+        anyhow::bail!(error);
+    }
+
+    let tabs = response.tabs.unwrap_or_default();
+    let shown = match tab {
+        // an explicitly chosen tab is shown whatever it is doing
+        Some(_) => tabs.into_iter().next(),
+        None => tabs
+            .into_iter()
+            .filter(|(_, status)| priority(status.state) > 0)
+            .max_by_key(|(_, status)| priority(status.state)),
+    };
+    Ok(shown)
+}
+
+/// Which tab wins when several are active, higher first. Idle tabs are not shown.
+fn priority(state: TimerState) -> u8 {
+    match state {
+        TimerState::Ringing => 4,
+        TimerState::Overtime => 3,
+        TimerState::Running => 2,
+        TimerState::Paused => 1,
+        TimerState::Idle | TimerState::Setup | TimerState::Armed => 0,
+    }
+}
+
+fn format_line(options: &StatusOptions, shown: Option<&Shown>) -> String {
+    let text = shown.map(|(tab, status)| render(&options.template, tab, status)).unwrap_or_default();
+    let state = shown.map_or("idle", |(_, status)| state_name(status.state));
+
+    match options.format {
+        StatusFormat::Plain => text,
+        StatusFormat::Waybar => {
+            let tooltip = shown.map(|(tab, status)| render("{tab}: {state} {label} {time}", tab, status));
+            json!({
+                "text": text,
+                "tooltip": tooltip.unwrap_or_default().split_whitespace().collect::<Vec<_>>().join(" "),
+                "class": state,
+                "alt": shown.map_or("", |(tab, _)| tab.as_str()),
+            })
+            .to_string()
+        }
+        StatusFormat::Tmux if text.is_empty() => text,
+        StatusFormat::Tmux => {
+            let color = match shown.map(|(_, status)| status.state) {
+                Some(TimerState::Running) => "green",
+                Some(TimerState::Overtime) => "magenta",
+                Some(TimerState::Ringing) => "red,blink",
+                _ => "yellow",
+            };
+            format!("#[fg={color}]{text}#[default]")
+        }
+        StatusFormat::I3bar => unreachable!("i3bar blocks are built by i3bar_block"),
+    }
+}
+
+fn i3bar_block(template: &str, shown: Option<&Shown>) -> serde_json::Value {
+    let text = shown.map(|(tab, status)| render(template, tab, status)).unwrap_or_default();
+    let urgent = shown.is_some_and(|(_, status)| matches!(status.state, TimerState::Ringing | TimerState::Overtime));
+
+    json!({
+        "name": "timemann",
+        "instance": shown.map_or("", |(tab, _)| tab.as_str()),
+        "full_text": text,
+        "urgent": urgent,
+    })
+}
+
+fn state_name(state: TimerState) -> &'static str {
+    match state {
+        TimerState::Idle => "idle",
+        TimerState::Setup => "setup",
+        TimerState::Running => "running",
+        TimerState::Paused => "paused",
+        TimerState::Overtime => "overtime",
+        TimerState::Armed => "armed",
+        TimerState::Ringing => "ringing",
+    }
+}
+
+/// Fills in a template such as `{label} {remaining:%M:%S}`. The placeholders are `tab`,
+/// `state`, `label`, `elapsed`, `remaining` and `time`, which is the remaining time when
+/// there is one and the elapsed time otherwise. Times are written with `format_duration`
+/// unless a format follows the colon. The result is trimmed.
+pub fn render(template: &str, tab: &str, status: &TabStatus) -> String {
+    // a countdown reads 00:00:01 until it is over, so its time is rounded up
+    let remaining = status.remaining.map(|remaining| Duration::from_secs(remaining.as_secs_f64().ceil() as u64));
+    let time = remaining.or(status.elapsed);
+
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let placeholder = &rest[start + 1..start + end];
+        let (name, spec) = match placeholder.split_once(':') {
+            Some((name, spec)) => (name, Some(spec)),
+            None => (placeholder, None),
+        };
+        let duration = |duration: Option<Duration>| match (duration, spec) {
+            (Some(duration), Some(spec)) => format_spec(duration, spec),
+            (Some(duration), None) => format_duration(duration),
+            (None, _) => String::new(),
+        };
+
+        match name {
+            "tab" => output.push_str(tab),
+            "state" => output.push_str(state_name(status.state)),
+            "label" => output.push_str(&status.label),
+            "elapsed" => output.push_str(&duration(status.elapsed)),
+            "remaining" => output.push_str(&duration(remaining)),
+            "time" => output.push_str(&duration(time)),
+            _ => output.push_str(&rest[start..=start + end]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+
+    output.trim().to_string()
+}
+
+/// Formats a duration by a spec such as `%M:%S`, where `%d` is days, `%H` hours, `%M`
+/// minutes and `%S` seconds, all zero-padded. The largest unit used takes the whole
+/// duration, so `%M:%S` shows 90 minutes as `90:00`.
+fn format_spec(duration: Duration, spec: &str) -> String {
+    let total = duration.as_secs();
+    let largest = ['d', 'H', 'M', 'S']
+        .into_iter()
+        .find(|unit| spec.contains(&format!("%{unit}")))
+        .unwrap_or('S');
+
+    let value = |unit: char| match (unit, largest) {
+        ('d', _) => total / 86400,
+        ('H', 'H') => total / 3600,
+        ('H', _) => (total / 3600) % 24,
+        ('M', 'M') => total / 60,
+        ('M', _) => (total / 60) % 60,
+        ('S', 'S') => total,
+        _ => total % 60,
+    };
+
+    let mut output = String::new();
+    let mut chars = spec.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('%', Some(unit @ ('d' | 'H' | 'M' | 'S'))) => {
+                output.push_str(&format!("{:02}", value(unit)));
+                chars.next();
+            }
+            ('%', Some('%')) => {
+                output.push('%');
+                chars.next();
+            }
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::secs;
+
+    fn status(elapsed: Option<Duration>, remaining: Option<Duration>) -> TabStatus {
+        TabStatus {
+            state: TimerState::Running,
+            label: String::from("Tea"),
+            elapsed,
+            remaining,
+        }
+    }
+
+    #[test]
+    fn largest_unit_takes_the_whole_duration() {
+        let ninety_minutes = secs(90 * 60);
+        assert_eq!(format_spec(ninety_minutes, "%M:%S"), "90:00");
+        assert_eq!(format_spec(ninety_minutes, "%H:%M:%S"), "01:30:00");
+        assert_eq!(format_spec(ninety_minutes, "%S"), "5400");
+        assert_eq!(format_spec(secs(26 * 3600 + 65), "%dd %H:%M:%S"), "01d 02:01:05");
+        assert_eq!(format_spec(secs(26 * 3600), "%Hh"), "26h");
+    }
+
+    #[test]
+    fn percent_signs() {
+        assert_eq!(format_spec(secs(5), "%S%%"), "05%");
+        assert_eq!(format_spec(secs(5), "%x %S"), "%x 05");
+        assert_eq!(format_spec(secs(5), "%S%"), "05%");
+    }
+
+    #[test]
+    fn placeholders() {
+        let running = status(Some(secs(30)), Some(secs(90 * 60)));
+        assert_eq!(render("{tab}: {state} {label}", "countdown", &running), "countdown: running Tea");
+        assert_eq!(render("{label} {remaining:%M:%S}", "countdown", &running), "Tea 90:00");
+        assert_eq!(render("{elapsed:%M:%S} of {time:%H:%M}", "countdown", &running), "00:30 of 01:30");
+
+        let stopwatch = status(Some(secs(75)), None);
+        assert_eq!(render("{time:%M:%S}", "stopwatch", &stopwatch), "01:15");
+        assert_eq!(render("{label} {remaining:%M:%S}", "stopwatch", &stopwatch), "Tea");
+    }
+
+    #[test]
+    fn remaining_time_is_rounded_up() {
+        let countdown = status(None, Some(Duration::from_millis(59_200)));
+        assert_eq!(render("{remaining:%M:%S}", "countdown", &countdown), "01:00");
+
+        let stopwatch = status(Some(Duration::from_millis(59_900)), None);
+        assert_eq!(render("{elapsed:%M:%S}", "stopwatch", &stopwatch), "00:59");
+    }
+
+    #[test]
+    fn unknown_and_unclosed_placeholders_are_kept() {
+        let running = status(None, Some(secs(60)));
+        assert_eq!(render("{label} {nope} {remaining:%S}", "countdown", &running), "Tea {nope} 60");
+        assert_eq!(render("{label} {remaining", "countdown", &running), "Tea {remaining");
+        assert_eq!(render("{} {label}", "countdown", &running), "{} Tea");
+    }
+}
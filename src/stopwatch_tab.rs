@@ -1,37 +1,92 @@
+use std::time::Duration;
+
 use anyhow::{Ok, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tui_big_text::BigText;
 use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+
+use crate::clock::SharedClock;
+use crate::colors::theme;
+use crate::history::{Outcome, SessionRecord};
+use crate::hooks::{HookEvent, HookKind};
+use crate::tabs::{AppTab, TabStatus, TimerState};
+use crate::ui::{flash_line, format_adjustment, format_duration, next_change, setup_big_timer_text, sooner, Flash};
+use crate::{msg::Message, states::AppState, timer::{Timer, TimerSnapshot}};
 
-use crate::tabs::AppTab;
-use crate::ui::{format_duration, setup_big_timer_text};
-use crate::{msg::Message, states::AppState, timer::Timer};
 
+/// A single recorded lap: its own length and the total time at which it ended.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Lap {
+    pub time: Duration,
+    pub split: Duration,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedStopwatch {
+    timer: TimerSnapshot,
+    laps: Vec<Lap>,
+    #[serde(default)]
+    started_at: Option<DateTime<Utc>>,
+}
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct StopwatchTab {
     state: AppState,
     timer: Timer,
+    laps: Vec<Lap>,
+    laps_state: TableState,
+    /// Wall time of the first start since the last clear
+    started_at: Option<DateTime<Utc>>,
+    records: Vec<SessionRecord>,
+    hook_events: Vec<HookEvent>,
+    /// Feedback for the last adjustment
+    flash: Option<Flash>,
 }
 
 impl StopwatchTab {
 
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            timer: Timer::with_clock(clock),
+            ..Self::default()
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.timer.elapsed()
+    }
+
     fn clear(&mut self) {
         if self.state.is_stopped() {
+            self.record();
             self.timer.reset();
+            self.laps.clear();
+            self.laps_state = TableState::default();
         }
     }
 
     fn toggle_start_pause(&mut self) {
-        if self.state.is_stopped() {
+        let kind = if self.state.is_stopped() {
             self.start();
+            HookKind::Start
         } else {
             self.stop();
-        }
+            HookKind::Pause
+        };
+        self.hook_events.push(HookEvent {
+            elapsed: Some(self.timer.elapsed()),
+            ..HookEvent::new(kind, "")
+        });
     }
 
     fn start(&mut self) {
         self.timer.start();
         self.state = AppState::Running;
+        let now = self.timer.clock().utc();
+        self.started_at.get_or_insert(now);
     }
 
     fn stop(&mut self) {
@@ -39,64 +94,383 @@ impl StopwatchTab {
         self.state = AppState::Stopped;
     }
 
+    /// A stopwatch has no target, so clearing it is how a session ends.
+    fn record(&mut self) {
+        let Some(started_at) = self.started_at.take() else {
+            return;
+        };
+
+        let label = match self.laps.len() {
+            0 => String::new(),
+            1 => String::from("1 lap"),
+            count => format!("{count} laps"),
+        };
+        self.records.push(SessionRecord::new(
+            "stopwatch",
+            label,
+            started_at,
+            self.timer.clock().utc(),
+            self.timer.elapsed(),
+            Outcome::Completed,
+        ));
+    }
+
+    /// Moves the elapsed time forwards or back without pausing, e.g. for a late start.
+    fn adjust(&mut self, seconds: i64) {
+        if !self.state.is_running() {
+            return;
+        }
+
+        let amount = Duration::from_secs(seconds.unsigned_abs());
+        let elapsed = if seconds >= 0 {
+            self.timer.elapsed() + amount
+        } else {
+            self.timer.elapsed().saturating_sub(amount)
+        };
+
+        self.timer.set_elapsed(elapsed);
+        self.flash = Some(Flash::new(format_adjustment(seconds), self.timer.clock().now()));
+    }
+
+    fn lap(&mut self) {
+        if !self.state.is_running() {
+            return;
+        }
+
+        let split = self.timer.elapsed();
+        let previous_split = self.laps.last().map_or(Duration::ZERO, |lap| lap.split);
+
+        let lap = Lap {
+            time: split.saturating_sub(previous_split),
+            split,
+        };
+        self.laps.push(lap);
+        self.hook_events.push(HookEvent {
+            duration: Some(lap.time),
+            elapsed: Some(lap.split),
+            ..HookEvent::new(HookKind::Lap, format!("Lap {}", self.laps.len()))
+        });
+        // newest lap is shown on top, so jump back to it
+        *self.laps_state.offset_mut() = 0;
+    }
+
+    fn scroll_up(&mut self) {
+        let offset = self.laps_state.offset_mut();
+        *offset = offset.saturating_sub(1);
+    }
+
+    fn scroll_down(&mut self) {
+        let max_offset = self.laps.len().saturating_sub(1);
+        let offset = self.laps_state.offset_mut();
+        *offset = (*offset + 1).min(max_offset);
+    }
+
+    /// Indexes of the fastest and slowest laps, if there are at least two to compare.
+    fn lap_extremes(&self) -> Option<(usize, usize)> {
+        if self.laps.len() < 2 {
+            return None;
+        }
+
+        let (fastest, _) = self.laps.iter().enumerate().min_by_key(|(_, lap)| lap.time)?;
+        let (slowest, _) = self.laps.iter().enumerate().max_by_key(|(_, lap)| lap.time)?;
+        Some((fastest, slowest))
+    }
+
     fn timer_paragraph(&mut self, area: Rect) -> BigText<'_> {
         setup_big_timer_text(
-            format_duration(self.timer.elapsed()), 
-            self.state, 
+            format_duration(self.timer.elapsed()),
+            self.state,
             area.width
         )
     }
 
+    fn laps_table(&self) -> Table<'_> {
+        let extremes = self.lap_extremes();
+
+        let rows = self.laps
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, lap)| {
+                let delta = match index {
+                    0 => String::from("-"),
+                    _ => format_lap_delta(lap.time, self.laps[index - 1].time),
+                };
+
+                let style = match extremes {
+                    Some((fastest, _)) if fastest == index => Style::new().green(),
+                    Some((_, slowest)) if slowest == index => Style::new().red(),
+                    _ => Style::new(),
+                };
+
+                Row::new(vec![
+                    Cell::from(format!("{:>3}", index + 1)),
+                    Cell::from(format_duration(lap.time)),
+                    Cell::from(format_duration(lap.split)),
+                    Cell::from(delta),
+                ])
+                .style(style)
+            });
+
+        let widths = [
+            Constraint::Length(3),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(9),
+        ];
+
+        Table::new(rows, widths)
+            .header(Row::new(vec![" #", "Lap", "Split", "Delta"]).bold())
+            .column_spacing(2)
+            .block(Block::default().borders(Borders::LEFT).dim())
+            .bg(theme().main_background)
+    }
+
 }
 
-pub fn layout(area: Rect) -> Vec<Rect> {
+/// Formats the difference between a lap and the one before it as `+s.mmm` / `-s.mmm`.
+fn format_lap_delta(current: Duration, previous: Duration) -> String {
+    let (sign, diff) = if current >= previous {
+        ('+', current - previous)
+    } else {
+        ('-', previous - current)
+    };
+
+    format!("{}{}.{:03}", sign, diff.as_secs(), diff.subsec_millis())
+}
+
+pub fn layout(area: Rect, with_laps: bool) -> Vec<Rect> {
+    let columns = if with_laps {
+        Layout::horizontal(vec![
+            Constraint::Min(0), // timer
+            Constraint::Length(47), // laps
+        ])
+        .split(area)
+    } else {
+        Layout::horizontal(vec![Constraint::Min(0)]).split(area)
+    };
+
     let layout = Layout::vertical(vec![
         Constraint::Percentage(100), // INGORE
         Constraint::Min(8), // timer
+        Constraint::Length(1), // flash
         Constraint::Percentage(100), // INGORE
     ])
-    .split(area);
+    .split(columns[0]);
 
-    layout[1..]
+    layout[1..3]
         .iter()
+        .chain(columns[1..].iter())
         .copied()
         .collect()
 }
 
 impl AppTab for StopwatchTab {
+    fn id(&self) -> &'static str {
+        "stopwatch"
+    }
+
+    fn title(&self) -> &'static str {
+        "Stopwatch"
+    }
+
+    fn icon(&self) -> &'static str {
+        "⌚"
+    }
+
     fn handle_message(&mut self, message: Message) {
         match message {
             Message::ToggleStartPause => self.toggle_start_pause(),
             Message::Clear => self.clear(),
-            // Message::Stop => self.stop(),
+            Message::Lap => self.lap(),
+            Message::ScrollUp => self.scroll_up(),
+            Message::ScrollDown => self.scroll_down(),
+            Message::Adjust(seconds) => self.adjust(seconds),
             _ => (),
         }
     }
-    
+
     fn render_paragraph(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
-        let area = layout(*area);
+        let area = layout(*area, !self.laps.is_empty());
         frame.render_widget(self.timer_paragraph(area[0]), area[0]);
+        frame.render_widget(Paragraph::new(flash_line(&self.flash, self.timer.clock().now())).centered(), area[1]);
+
+        if let Some(laps_area) = area.get(2) {
+            let mut laps_state = self.laps_state.clone();
+            frame.render_stateful_widget(self.laps_table(), *laps_area, &mut laps_state);
+        }
         Ok(())
     }
 
-    fn help_text(&mut self) -> Vec<Span> {
-        let enter_action = if self.state.is_stopped() {
-            " Start "
+    fn redraw_in(&self) -> Option<Duration> {
+        let timer = self.state.is_running().then(|| next_change(self.timer.elapsed(), false));
+        sooner(timer, self.flash.as_ref().and_then(|flash| flash.remaining(self.timer.clock().now())))
+    }
+
+    fn status(&self) -> Option<TabStatus> {
+        let state = if self.state.is_running() {
+            TimerState::Running
+        } else if self.timer.elapsed().is_zero() {
+            TimerState::Idle
         } else {
-            " Pause "
+            TimerState::Paused
         };
 
-        let clear_part = if self.state.is_stopped() && !self.timer.elapsed().is_zero() {
-            ( " C ".on_dark_gray().black().dim(), " Clear ".dim() )
-        } else {
-            ( "".on_dark_gray().black().dim(), "".dim() )
+        Some(TabStatus {
+            state,
+            label: String::new(),
+            elapsed: Some(self.timer.elapsed()),
+            remaining: None,
+        })
+    }
+
+    fn save_state(&self) -> Option<Value> {
+        let saved = SavedStopwatch {
+            timer: self.timer.snapshot(),
+            laps: self.laps.clone(),
+            started_at: self.started_at,
         };
+        serde_json::to_value(saved).ok()
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<Option<String>> {
+        let saved: SavedStopwatch = serde_json::from_value(state)?;
+        self.timer = Timer::from_snapshot(saved.timer, self.timer.clock().clone());
+        self.state = if saved.timer.running { AppState::Running } else { AppState::Stopped };
+        self.laps = saved.laps;
+        self.started_at = saved.started_at;
+        Ok(None)
+    }
+
+    fn take_records(&mut self) -> Vec<SessionRecord> {
+        std::mem::take(&mut self.records)
+    }
+
+    fn take_hook_events(&mut self) -> Vec<HookEvent> {
+        std::mem::take(&mut self.hook_events)
+    }
+
+    fn help_entries(&self) -> Vec<(Message, &'static str)> {
+        let mut entries = vec![(
+            Message::ToggleStartPause,
+            if self.state.is_stopped() { "Start" } else { "Pause" },
+        )];
+
+        if self.state.is_running() {
+            entries.push((Message::Lap, "Lap"));
+            entries.push((Message::Adjust(60), "Adjust"));
+            entries.push((Message::Adjust(-60), "Adjust"));
+        }
+
+        if self.laps.len() > 1 {
+            entries.push((Message::ScrollUp, "Scroll Laps"));
+            entries.push((Message::ScrollDown, "Scroll Laps"));
+        }
+
+        if self.state.is_stopped() && !self.timer.elapsed().is_zero() {
+            entries.push((Message::Clear, "Clear"));
+        }
+
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::clock::{secs, Clock, FakeClock, SharedClock};
+
+    fn stopwatch() -> (Arc<FakeClock>, StopwatchTab) {
+        let clock = FakeClock::new();
+        let stopwatch = StopwatchTab::with_clock(SharedClock::new(clock.clone()));
+        (clock, stopwatch)
+    }
+
+    #[test]
+    fn starts_pauses_and_clears() {
+        let (clock, mut stopwatch) = stopwatch();
+        stopwatch.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(3));
+        stopwatch.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(60));
+        assert_eq!(stopwatch.elapsed(), secs(3));
+        assert_eq!(stopwatch.status().unwrap().state, TimerState::Paused);
+
+        stopwatch.handle_message(Message::Clear);
+        assert_eq!(stopwatch.elapsed(), Duration::ZERO);
+        assert_eq!(stopwatch.status().unwrap().state, TimerState::Idle);
+
+        let records = stopwatch.take_records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].duration, secs(3));
+        assert_eq!(records[0].ended_at, clock.utc());
+    }
+
+    #[test]
+    fn clear_is_ignored_while_running() {
+        let (clock, mut stopwatch) = stopwatch();
+        stopwatch.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(3));
+        stopwatch.handle_message(Message::Clear);
+
+        assert_eq!(stopwatch.elapsed(), secs(3));
+        assert!(stopwatch.take_records().is_empty());
+    }
 
-        vec![
-            " Enter ".on_dark_gray().black().dim(),
-            enter_action.dim(),
-            clear_part.0,
-            clear_part.1
-        ]
+    #[test]
+    fn laps_record_time_and_split() {
+        let (clock, mut stopwatch) = stopwatch();
+        stopwatch.handle_message(Message::ToggleStartPause);
+        for lap in [10, 7, 12] {
+            clock.advance(secs(lap));
+            stopwatch.handle_message(Message::Lap);
+        }
+
+        let times: Vec<_> = stopwatch.laps.iter().map(|lap| (lap.time, lap.split)).collect();
+        assert_eq!(times, [(secs(10), secs(10)), (secs(7), secs(17)), (secs(12), secs(29))]);
+        assert_eq!(stopwatch.lap_extremes(), Some((1, 2)));
+        assert_eq!(format_lap_delta(secs(7), secs(10)), "-3.000");
+    }
+
+    #[test]
+    fn laps_are_ignored_while_paused() {
+        let (_, mut stopwatch) = stopwatch();
+        stopwatch.handle_message(Message::Lap);
+        assert!(stopwatch.laps.is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn adjust_moves_a_running_stopwatch() {
+        let (clock, mut stopwatch) = stopwatch();
+        stopwatch.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(30));
+
+        stopwatch.handle_message(Message::Adjust(60));
+        clock.advance(secs(1));
+        assert_eq!(stopwatch.elapsed(), secs(91));
+        assert_eq!(stopwatch.flash.as_ref().unwrap().text(clock.now()), Some("+1m"));
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(stopwatch.flash.as_ref().unwrap().text(clock.now()), None);
+
+        stopwatch.handle_message(Message::Adjust(-600));
+        assert_eq!(stopwatch.elapsed(), Duration::ZERO);
+        assert!(stopwatch.state.is_running());
+    }
+
+    #[test]
+    fn adjust_is_ignored_unless_running() {
+        let (clock, mut stopwatch) = stopwatch();
+        stopwatch.handle_message(Message::Adjust(60));
+        assert_eq!(stopwatch.elapsed(), Duration::ZERO);
+        assert!(stopwatch.flash.is_none());
+
+        stopwatch.handle_message(Message::ToggleStartPause);
+        clock.advance(secs(5));
+        stopwatch.handle_message(Message::ToggleStartPause);
+        stopwatch.handle_message(Message::Adjust(60));
+        assert_eq!(stopwatch.elapsed(), secs(5));
+    }
+}
@@ -1,84 +1,555 @@
+use std::collections::BTreeMap;
 use std::fmt::Debug;
+use std::time::Duration;
 
 use ratatui::widgets::Tabs;
 use ratatui::prelude::*;
-use anyhow::Result;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use strum::EnumIs;
 
 use crate::about_tab::AboutTab;
-use crate::colors::BG_COLOR;
+use crate::alarm_tab::AlarmTab;
+use crate::clock::SharedClock;
+use crate::colors::theme;
+use crate::config::{Config, TabsConfig};
 use crate::countdown_tab::CountdownTab;
+use crate::duration::optional_millis;
+use crate::history::SessionRecord;
+use crate::history_tab::HistoryTab;
+use crate::hooks::HookEvent;
+use crate::interval_tab::IntervalTab;
+use crate::keymap::{TextInput, RINGING_CONTEXT};
 use crate::msg::Message;
+use crate::pomodoro_tab::PomodoroTab;
+use crate::session::Session;
 use crate::stopwatch_tab::StopwatchTab;
+use crate::timers_tab::TimersTab;
+use crate::ui::sooner;
+use crate::world_clock_tab::WorldClockTab;
+
+/// What a tab's timer is doing, as reported to scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIs, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerState {
+    /// Not started, or cleared
+    Idle,
+    /// Waiting for a duration to be typed
+    Setup,
+    Running,
+    Paused,
+    /// A countdown that ran past zero
+    Overtime,
+    /// An alarm is set to go off
+    Armed,
+    /// An alarm is going off
+    Ringing,
+}
+
+/// A tab's timer as reported over the control socket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabStatus {
+    pub state: TimerState,
+    /// e.g. the pomodoro phase or the timer's name
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label: String,
+    #[serde(rename = "elapsed_ms", default, skip_serializing_if = "Option::is_none", with = "optional_millis")]
+    pub elapsed: Option<Duration>,
+    /// Time left on a countdown, the current interval or until the next alarm
+    #[serde(rename = "remaining_ms", default, skip_serializing_if = "Option::is_none", with = "optional_millis")]
+    pub remaining: Option<Duration>,
+}
+
+/// Builds one of the tabs, telling time by the given clock.
+type NewTab = fn(SharedClock) -> Box<dyn AppTab>;
+
+/// Every tab there is by id, in the order they are shown unless `[tabs]` says otherwise.
+/// The ids are those of `AppTab::id`, so they are known without building the tabs.
+const REGISTRY: &[(&str, NewTab)] = &[
+    ("stopwatch", |clock| Box::new(StopwatchTab::with_clock(clock))),
+    ("countdown", |clock| Box::new(CountdownTab::with_clock(clock))),
+    ("timers", |clock| Box::new(TimersTab::with_clock(clock))),
+    ("alarms", |clock| Box::new(AlarmTab::with_clock(clock))),
+    ("clocks", |clock| Box::new(WorldClockTab::with_clock(clock))),
+    ("pomodoro", |clock| Box::new(PomodoroTab::with_clock(clock))),
+    ("intervals", |clock| Box::new(IntervalTab::with_clock(clock))),
+    ("history", |clock| Box::new(HistoryTab::with_clock(clock))),
+    ("about", |_| Box::new(AboutTab::default())),
+];
+
+/// Ids of every tab, in the default order.
+pub fn tab_ids() -> Vec<&'static str> {
+    REGISTRY.iter().map(|(id, _)| *id).collect()
+}
+
+/// Ids of the tabs `[tabs]` asks for, in order.
+pub fn enabled_tab_ids(config: &TabsConfig) -> Result<Vec<&'static str>> {
+    let ids = tab_ids();
+    let find = |name: &str| match ids.iter().find(|id| id.eq_ignore_ascii_case(name)) {
+        Some(id) => Ok(*id),
+        None => bail!("unknown tab '{}' in [tabs], expected one of: {}", name, ids.join(", ")),
+    };
+
+    let order = match &config.order {
+        Some(order) => order.iter().map(|name| find(name)).collect::<Result<Vec<_>>>()?,
+        None => ids.clone(),
+    };
+    let disabled = config.disabled.iter().map(|name| find(name)).collect::<Result<Vec<_>>>()?;
+
+    let mut enabled = vec![];
+    for id in order {
+        if enabled.contains(&id) {
+            bail!("tab '{id}' appears twice in tabs.order");
+        }
+        if !disabled.contains(&id) {
+            enabled.push(id);
+        }
+    }
+    if enabled.is_empty() {
+        bail!("[tabs] leaves no tab to show");
+    }
+    Ok(enabled)
+}
 
 #[derive(Debug, Clone)]
 pub struct TabGroup {
     current_tab_index: usize,
-    titles: Vec<String>,
+    /// The tabs shown, in order
     tabs: Vec<Box<dyn AppTab>>,
+    /// Tabs left out by the config. They keep their state, but are not ticked until
+    /// enabled again.
+    disabled: Vec<Box<dyn AppTab>>,
+    /// Whether titles are preceded by the tab's icon
+    icons: bool,
 }
 
 impl TabGroup {
-    pub fn new() -> Self {
+    /// All tabs, telling time by `clock`.
+    pub fn new(clock: SharedClock) -> Self {
         Self {
             current_tab_index: 0,
-            titles: vec![
-                "Stopwatch".to_string(),
-                "Countdown".to_string(),
-                "About".to_string()
-            ],
-            tabs: vec![
-                Box::new(StopwatchTab::default()),
-                Box::new(CountdownTab::default()),
-                Box::new(AboutTab::default()),
-            ]
+            tabs: REGISTRY.iter().map(|(_, new_tab)| new_tab(clock.clone())).collect(),
+            disabled: vec![],
+            icons: false,
         }
     }
 
     pub fn widget(&self) -> Tabs<'_> {
-        let titles = self.titles.clone();
+        let titles: Vec<String> = self.tabs
+            .iter()
+            .map(|tab| match self.icons {
+                true => format!("{} {}", tab.icon(), tab.title()),
+                false => tab.title().to_string(),
+            })
+            .collect();
         Tabs::new(titles)
             .style(Style::default().white())
             .select(self.current_tab_index)
             .divider(" ")
             .padding(" ", " ")
-            .bg(BG_COLOR)
+            .bg(theme().background)
     }
 
     pub fn increase_index(&mut self) {
         self.current_tab_index += 1;
 
-        if self.current_tab_index >= self.titles.len() {
+        if self.current_tab_index >= self.tabs.len() {
             self.current_tab_index = 0;
         }
     }
 
+    /// Switches to the tab whose id matches `name`, ignoring case.
+    pub fn select(&mut self, name: &str) -> Result<()> {
+        self.current_tab_index = self.index_of(name)?;
+        Ok(())
+    }
+
+    /// Keymap context of the active tab, its id, or `ringing` while a tab shows a modal.
+    pub fn current_context(&self) -> String {
+        match self.modal_index() {
+            Some(_) => RINGING_CONTEXT.to_string(),
+            None => self.tabs[self.current_tab_index].id().to_string(),
+        }
+    }
+
     pub fn current_tab(&mut self) -> &mut Box<dyn AppTab> {
         &mut self.tabs[self.current_tab_index]
     }
+
+    /// Index of the tab whose id matches `name`, ignoring case.
+    fn index_of(&self, name: &str) -> Result<usize> {
+        if let Some(index) = self.tabs.iter().position(|tab| tab.id().eq_ignore_ascii_case(name)) {
+            return Ok(index);
+        }
+        if self.disabled.iter().any(|tab| tab.id().eq_ignore_ascii_case(name)) {
+            bail!("the {} tab is disabled in the config", name.to_lowercase());
+        }
+
+        let ids: Vec<&str> = self.tabs.iter().map(|tab| tab.id()).collect();
+        bail!("unknown tab '{}', expected one of: {}", name, ids.join(", "))
+    }
+
+    /// The tab whose id matches `name`, ignoring case.
+    pub fn tab_named(&mut self, name: &str) -> Result<&mut Box<dyn AppTab>> {
+        let index = self.index_of(name)?;
+        Ok(&mut self.tabs[index])
+    }
+
+    /// Status of the tab named `name`, or of every tab that has a timer, keyed by tab id.
+    pub fn status(&self, name: Option<&str>) -> Result<BTreeMap<String, TabStatus>> {
+        let indexes = match name {
+            Some(name) => vec![self.index_of(name)?],
+            None => (0..self.tabs.len()).collect(),
+        };
+
+        Ok(indexes
+            .into_iter()
+            .filter_map(|index| Some((self.tabs[index].id().to_string(), self.tabs[index].status()?)))
+            .collect())
+    }
+
+    /// Index of the first tab showing a modal, e.g. a ringing alarm.
+    fn modal_index(&self) -> Option<usize> {
+        self.tabs.iter().position(|tab| tab.has_modal())
+    }
+
+    /// The tab that receives input: one showing a modal, otherwise the current tab.
+    pub fn active_tab(&mut self) -> &mut Box<dyn AppTab> {
+        let index = self.modal_index().unwrap_or(self.current_tab_index);
+        &mut self.tabs[index]
+    }
+
+    /// Time until the tabs on screen, the current one and any modal, change on their own.
+    pub fn redraw_in(&self) -> Option<Duration> {
+        let modal = self.modal_index().and_then(|index| self.tabs[index].redraw_in());
+        sooner(self.tabs[self.current_tab_index].redraw_in(), modal)
+    }
+
+    /// Time until any tab changes on its own, shown, hidden or disabled, so timers in the
+    /// background are ticked in time to end.
+    pub fn tick_in(&self) -> Option<Duration> {
+        self.tabs.iter().chain(self.disabled.iter()).filter_map(|tab| tab.redraw_in()).min()
+    }
+
+    /// Whether a tab on screen changed on its own since the last call.
+    pub fn take_changed(&mut self) -> bool {
+        let shown = [Some(self.current_tab_index), self.modal_index()];
+        let mut changed = false;
+        // every tab is asked, so a change in the background does not linger until it is shown
+        for (index, tab) in self.tabs.iter_mut().enumerate() {
+            changed |= tab.take_changed() && shown.contains(&Some(index));
+        }
+        for tab in self.disabled.iter_mut() {
+            tab.take_changed();
+        }
+        changed
+    }
+
+    /// Draws the modal of the active tab over `area`, if there is one.
+    pub fn render_modal(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
+        match self.modal_index() {
+            Some(index) => self.tabs[index].render_modal(frame, area),
+            None => Ok(()),
+        }
+    }
+
+    /// Shows the tabs `[tabs]` asks for, in its order, and passes the config on to every
+    /// tab, disabled ones included.
+    pub fn apply_config(&mut self, config: &Config) {
+        // the config was validated when loaded, so the ids cannot be wrong here
+        let enabled = enabled_tab_ids(&config.tabs).unwrap_or_else(|_| tab_ids());
+        let current = self.tabs.get(self.current_tab_index).map(|tab| tab.id());
+
+        let mut all: Vec<Box<dyn AppTab>> = self.tabs.drain(..).chain(self.disabled.drain(..)).collect();
+        for id in enabled {
+            if let Some(index) = all.iter().position(|tab| tab.id() == id) {
+                self.tabs.push(all.remove(index));
+            }
+        }
+        self.disabled = all;
+        self.icons = config.tabs.icons;
+        self.current_tab_index = current
+            .and_then(|id| self.tabs.iter().position(|tab| tab.id() == id))
+            .unwrap_or(0);
+
+        for tab in self.tabs.iter_mut().chain(self.disabled.iter_mut()) {
+            tab.apply_config(config);
+        }
+    }
+
+    /// Collects the state of every tab that has something to save, disabled ones included
+    /// so they resume once enabled again.
+    pub fn save_session(&self) -> Session {
+        let tabs = self.tabs
+            .iter()
+            .chain(self.disabled.iter())
+            .filter_map(|tab| Some((tab.id().to_string(), tab.save_state()?)))
+            .collect();
+
+        Session { tabs }
+    }
+
+    /// Hands each tab its saved state, except the one named by `skip`.
+    /// Returns notices for the user, including tabs whose state could not be restored.
+    pub fn restore_session(&mut self, mut session: Session, skip: Option<&str>) -> Vec<String> {
+        let mut notices = vec![];
+        for tab in self.tabs.iter_mut().chain(self.disabled.iter_mut()) {
+            if skip == Some(tab.id()) {
+                continue;
+            }
+            let Some(state) = session.tabs.remove(tab.id()) else {
+                continue;
+            };
+
+            match tab.restore_state(state) {
+                Ok(Some(notice)) => notices.push(notice),
+                Ok(None) => (),
+                Err(err) => notices.push(format!("could not restore {}: {err:#}", tab.title())),
+            }
+        }
+        notices
+    }
+
+    /// Advances every tab, not only the visible one, so timers keep running in the background.
+    /// Disabled tabs are ticked too, timers restored into them or left running when they were
+    /// disabled still finish and alarms still ring.
+    pub fn tick_all(&mut self) {
+        for tab in self.tabs.iter_mut().chain(self.disabled.iter_mut()) {
+            tab.tick();
+        }
+    }
+
+    /// Collects the sessions every tab finished or cancelled since the last call,
+    /// disabled ones included.
+    pub fn take_records(&mut self) -> Vec<SessionRecord> {
+        self.tabs
+            .iter_mut()
+            .chain(self.disabled.iter_mut())
+            .flat_map(|tab| tab.take_records())
+            .collect()
+    }
+
+    /// Collects the hook events of every tab since the last call, with the id of the tab,
+    /// disabled ones included.
+    pub fn take_hook_events(&mut self) -> Vec<(String, HookEvent)> {
+        self.tabs
+            .iter_mut()
+            .chain(self.disabled.iter_mut())
+            .flat_map(|tab| {
+                let id = tab.id();
+                tab.take_hook_events().into_iter().map(move |event| (id.to_string(), event))
+            })
+            .collect()
+    }
+
+    /// Collects the notices of every tab since the last call, disabled ones included.
+    pub fn take_notices(&mut self) -> Vec<String> {
+        self.tabs
+            .iter_mut()
+            .chain(self.disabled.iter_mut())
+            .flat_map(|tab| tab.take_notices())
+            .collect()
+    }
 }
 
 impl Default for TabGroup {
     fn default() -> Self {
-        TabGroup::new()
+        TabGroup::new(SharedClock::default())
+    }
+}
+
+/// Lets a boxed tab be cloned, implemented for every tab that is `Clone`.
+pub trait CloneTab {
+    fn clone_tab(&self) -> Box<dyn AppTab>;
+}
+
+impl<T: AppTab + Clone + 'static> CloneTab for T {
+    fn clone_tab(&self) -> Box<dyn AppTab> {
+        Box::new(self.clone())
     }
 }
 
-pub trait AppTab {
+pub trait AppTab: CloneTab {
+    /// Names the tab in the config, on the command line and over the control socket,
+    /// and is its keymap context.
+    fn id(&self) -> &'static str;
+
+    /// Shown in the tab bar.
+    fn title(&self) -> &'static str;
+
+    /// Shown before the title with `tabs.icons`.
+    fn icon(&self) -> &'static str;
+
     fn handle_message(&mut self, message: Message);
 
+    /// Called on every `Message::Tick`, whether or not the tab is currently shown.
+    fn tick(&mut self) {}
+
+    /// Called on startup and whenever the config file is reloaded.
+    fn apply_config(&mut self, _config: &Config) {}
+
     fn render_paragraph(&mut self, frame: &mut Frame, area: &Rect) -> Result<()>;
 
-    fn help_text(&mut self) -> Vec<Span>;
+    /// Actions to list in the help bar with their labels. The keys shown next to them
+    /// come from the keymap.
+    fn help_entries(&self) -> Vec<(Message, &'static str)>;
+
+    /// State to save across restarts, `None` for tabs with nothing worth keeping.
+    fn save_state(&self) -> Option<Value> {
+        None
+    }
+
+    /// Restores what `save_state` returned on a previous run. May return a notice for the
+    /// user, e.g. about a countdown that ended while the app was closed.
+    fn restore_state(&mut self, _state: Value) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Sessions finished or cancelled since the last call, to be written to the history file.
+    fn take_records(&mut self) -> Vec<SessionRecord> {
+        vec![]
+    }
+
+    /// Timer events since the last call, to run the configured hooks for.
+    fn take_hook_events(&mut self) -> Vec<HookEvent> {
+        vec![]
+    }
+
+    /// Notices for the user since the last call, e.g. about an alarm missed while the
+    /// computer was asleep.
+    fn take_notices(&mut self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Time until what the tab shows changes on its own, e.g. the next digit of a running
+    /// timer. `None` while only input changes it.
+    fn redraw_in(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Whether the tab changed on its own in a way `redraw_in` could not foresee since the
+    /// last call, e.g. by reloading a file.
+    fn take_changed(&mut self) -> bool {
+        false
+    }
+
+    /// What the tab's timer is doing, `None` for tabs without one.
+    fn status(&self) -> Option<TabStatus> {
+        None
+    }
+
+    /// Whether the tab needs attention over whatever tab is shown, e.g. a ringing alarm.
+    /// While it does, all input goes to this tab.
+    fn has_modal(&self) -> bool {
+        false
+    }
+
+    /// Draws the modal over the whole screen, called only while `has_modal` is true.
+    fn render_modal(&mut self, _frame: &mut Frame, _area: &Rect) -> Result<()> {
+        Ok(())
+    }
+
+    /// Which printable keys should reach the tab as `Message::Input` instead of shortcuts.
+    fn text_input(&self) -> TextInput {
+        TextInput::Off
+    }
 }
 
 impl Debug for dyn AppTab {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "AppTab_Debug")
+        f.debug_tuple("AppTab").field(&self.id()).finish()
     }
 }
 
 impl Clone for Box<dyn AppTab> {
     fn clone(&self) -> Self {
-        Box::new(StopwatchTab::default())
+        (**self).clone_tab()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::hooks::HookKind;
+
+    fn ids(group: &TabGroup) -> Vec<&'static str> {
+        group.tabs.iter().map(|tab| tab.id()).collect()
+    }
+
+    fn tabs_config(order: Option<&[&str]>, disabled: &[&str]) -> Config {
+        let mut config = Config::default();
+        config.tabs.order = order.map(|order| order.iter().map(|id| id.to_string()).collect());
+        config.tabs.disabled = disabled.iter().map(|id| id.to_string()).collect();
+        config
+    }
+
+    #[test]
+    fn cloned_tabs_keep_their_kind_and_state() {
+        let clock = FakeClock::new();
+        let mut group = TabGroup::new(SharedClock::new(clock.clone()));
+        group.tab_named("stopwatch").unwrap().handle_message(Message::ToggleStartPause);
+        clock.advance(Duration::from_secs(5));
+
+        let clone = group.clone();
+        assert_eq!(ids(&clone), ids(&group));
+        assert_eq!(clone.status(None).unwrap(), group.status(None).unwrap());
+        assert_eq!(clone.status(Some("stopwatch")).unwrap()["stopwatch"].elapsed, Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn config_orders_and_disables_tabs() {
+        let mut group = TabGroup::default();
+        group.apply_config(&tabs_config(Some(&["countdown", "Stopwatch", "about"]), &["about"]));
+        assert_eq!(ids(&group), ["countdown", "stopwatch"]);
+
+        let err = group.select("about").unwrap_err();
+        assert_eq!(err.to_string(), "the about tab is disabled in the config");
+        let err = group.select("lap").unwrap_err();
+        assert_eq!(err.to_string(), "unknown tab 'lap', expected one of: countdown, stopwatch");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn reenabled_tabs_keep_their_state_and_the_selection() {
+        let mut group = TabGroup::default();
+        group.select("pomodoro").unwrap();
+        group.tab_named("stopwatch").unwrap().handle_message(Message::ToggleStartPause);
+
+        group.apply_config(&tabs_config(None, &["stopwatch"]));
+        assert!(!ids(&group).contains(&"stopwatch"));
+        assert_eq!(group.current_context(), "pomodoro");
+
+        group.apply_config(&Config::default());
+        assert_eq!(ids(&group), tab_ids());
+        assert_eq!(group.current_context(), "pomodoro");
+        assert_eq!(group.status(Some("stopwatch")).unwrap()["stopwatch"].state, TimerState::Running);
+    }
+
+    #[test]
+    fn disabled_tabs_keep_running() {
+        let clock = FakeClock::new();
+        let mut group = TabGroup::new(SharedClock::new(clock.clone()));
+        group.tab_named("pomodoro").unwrap().handle_message(Message::ToggleStartPause);
+        group.apply_config(&tabs_config(None, &["pomodoro"]));
+        let events = group.take_hook_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, "pomodoro");
+
+        clock.advance(Duration::from_secs(25 * 60));
+        group.tick_all();
+        assert_eq!(group.take_records().len(), 1);
+        let events = group.take_hook_events();
+        assert!(events.iter().any(|(id, event)| id == "pomodoro" && event.kind == HookKind::PhaseChange));
+    }
+
+    #[test]
+    fn invalid_tabs_config_is_rejected() {
+        let error = |order: &[&str], disabled: &[&str]| {
+            enabled_tab_ids(&tabs_config(Some(order), disabled).tabs).unwrap_err().to_string()
+        };
+        assert!(error(&["stopwatch", "lap"], &[]).starts_with("unknown tab 'lap' in [tabs]"));
+        assert_eq!(error(&["stopwatch", "stopwatch"], &[]), "tab 'stopwatch' appears twice in tabs.order");
+        assert_eq!(error(&["about"], &["about"]), "[tabs] leaves no tab to show");
+    }
+}
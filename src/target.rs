@@ -0,0 +1,298 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Datelike, Days, LocalResult, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::clock::LocalZone;
+
+
+/// A point in time to count down to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Target {
+    pub at: DateTime<Utc>,
+    /// Time zone named in the input, the local one is used when `None`
+    pub zone: Option<Tz>,
+}
+
+impl Target {
+    /// Time left until the target, `None` once it has passed.
+    pub fn remaining(&self, now: DateTime<Utc>) -> Option<Duration> {
+        (self.at - now).to_std().ok()
+    }
+
+    /// The target in the zone it was given in, or in `local` when none was named.
+    pub fn display(&self, local: LocalZone) -> String {
+        match self.zone {
+            Some(zone) => self.at.with_timezone(&zone).format("%a %d %b %Y %H:%M:%S %Z").to_string(),
+            None => self.at.with_timezone(&local).format("%a %d %b %Y %H:%M:%S").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Day {
+    Today,
+    Tomorrow,
+    Date(NaiveDate),
+    /// The next such weekday, skipping today when `next` was written
+    Weekday { weekday: Weekday, next: bool },
+}
+
+/// Whether typed countdown input names a point in time rather than a duration.
+/// A bare `17:30` reads as a duration, a time of day needs `until` or `at` in front.
+pub fn is_target(input: &str) -> bool {
+    let Some(first) = input.split_whitespace().next() else {
+        return false;
+    };
+    let first = first.to_ascii_lowercase();
+
+    matches!(first.as_str(), "until" | "at" | "today" | "tomorrow" | "next")
+        || first.parse::<Weekday>().is_ok()
+        || parse_date(&first).is_some()
+}
+
+/// Parses a point in time after `now`. Accepted forms are:
+///
+/// - a time of day such as `17:30` or `until 17:30:15`, today or else tomorrow
+/// - a date with an optional time, e.g. `2026-12-31 23:59:59` or `2026-12-31T23:59`
+/// - `today`, `tomorrow`, a weekday or `next <weekday>`, with an optional time
+///
+/// An IANA time zone such as `Europe/Berlin` may follow, otherwise `local` is used.
+/// Times that occur twice when the clocks go back pick the first, times skipped when
+/// they go forward are an error.
+pub fn parse_target(input: &str, now: DateTime<Utc>, local: LocalZone) -> Result<Target> {
+    let mut words: Vec<&str> = input.split_whitespace().collect();
+    if words.first().is_some_and(|word| matches!(word.to_ascii_lowercase().as_str(), "until" | "at")) {
+        words.remove(0);
+    }
+
+    let zone = match words.last().map(|word| word.parse::<Tz>()) {
+        Some(Ok(zone)) => {
+            words.pop();
+            Some(zone)
+        }
+        _ => None,
+    };
+
+    let mut day = None;
+    let mut time = None;
+    // `2026-12-31T23:59` is a date and a time written as one word
+    let mut words = words.into_iter().flat_map(|word| match word.split_once('T') {
+        Some((date, time)) if parse_date(date).is_some() => vec![date, time],
+        _ => vec![word],
+    });
+    while let Some(word) = words.next() {
+        let lower = word.to_ascii_lowercase();
+        let parsed_day = match lower.as_str() {
+            "today" => Some(Day::Today),
+            "tomorrow" => Some(Day::Tomorrow),
+            "next" => match words.next().map(|word| word.parse::<Weekday>()) {
+                Some(Ok(weekday)) => Some(Day::Weekday { weekday, next: true }),
+                _ => bail!("expected a weekday after 'next'"),
+            },
+            _ => match (lower.parse::<Weekday>(), parse_date(&lower)) {
+                (Ok(weekday), _) => Some(Day::Weekday { weekday, next: false }),
+                (_, Some(date)) => Some(Day::Date(date)),
+                _ => None,
+            },
+        };
+
+        if let Some(parsed) = parsed_day {
+            if day.replace(parsed).is_some() {
+                bail!("more than one day given");
+            }
+        } else if word.contains(':') {
+            if time.replace(parse_time(word)?).is_some() {
+                bail!("more than one time given");
+            }
+        } else if word.parse::<Tz>().is_ok() {
+            bail!("the time zone '{word}' must come last");
+        } else if word.contains('/') {
+            bail!("unknown time zone '{word}', expected a name such as Europe/Berlin");
+        } else {
+            bail!("unexpected '{word}', expected a date, a weekday or a time such as 17:30");
+        }
+    }
+
+    if day.is_none() && time.is_none() {
+        bail!("missing date or time");
+    }
+
+    let at = match zone {
+        Some(zone) => resolve(&zone, day, time, now)?,
+        None => resolve(&local, day, time, now)?,
+    };
+    if at <= now {
+        bail!("{} is in the past", at.with_timezone(&local).format("%Y-%m-%d %H:%M:%S"));
+    }
+    Ok(Target { at, zone })
+}
+
+fn parse_date(word: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d").ok()
+}
+
+fn parse_time(word: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(word, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(word, "%H:%M"))
+        .map_err(|_| anyhow::anyhow!("invalid time '{word}', expected hh:mm or hh:mm:ss"))
+}
+
+/// Picks the date the target falls on in `zone`. Without a day, or with a bare weekday,
+/// a time that already passed today moves on to the next possible day.
+fn resolve<Z: TimeZone>(zone: &Z, day: Option<Day>, time: Option<NaiveTime>, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let today = now.with_timezone(zone).date_naive();
+    let time = time.unwrap_or(NaiveTime::MIN);
+    let at = |date: NaiveDate| to_utc(zone, date, time);
+    let add_days = |date: NaiveDate, days: u64| date.checked_add_days(Days::new(days)).unwrap_or(date);
+
+    match day {
+        None => {
+            let at_today = at(today)?;
+            if at_today > now { Ok(at_today) } else { at(add_days(today, 1)) }
+        }
+        Some(Day::Today) => at(today),
+        Some(Day::Tomorrow) => at(add_days(today, 1)),
+        Some(Day::Date(date)) => at(date),
+        Some(Day::Weekday { weekday, next }) => {
+            let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            let date = add_days(today, ahead.into());
+            if ahead == 0 && (next || at(date)? <= now) {
+                at(add_days(date, 7))
+            } else {
+                at(date)
+            }
+        }
+    }
+}
+
+fn to_utc<Z: TimeZone>(zone: &Z, date: NaiveDate, time: NaiveTime) -> Result<DateTime<Utc>> {
+    let local = date.and_time(time);
+    match zone.from_local_datetime(&local) {
+        LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => Ok(at.with_timezone(&Utc)),
+        LocalResult::None => bail!("{local} does not exist in that time zone, the clocks skip it"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::{America, Europe};
+
+    use super::*;
+
+    /// Saturday, 2026-03-14 09:00 UTC.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 14, 9, 0, 0).unwrap()
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    fn target(input: &str, local: LocalZone) -> DateTime<Utc> {
+        parse_target(input, now(), local).unwrap_or_else(|err| panic!("{input}: {err}")).at
+    }
+
+    fn error(input: &str) -> String {
+        parse_target(input, now(), LocalZone::Fixed(Tz::UTC)).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn times_of_day_roll_over_to_tomorrow() {
+        let local = LocalZone::Fixed(Tz::UTC);
+        for (input, expected) in [
+            ("17:30", utc(2026, 3, 14, 17, 30)),
+            ("until 17:30", utc(2026, 3, 14, 17, 30)),
+            ("at 09:00:01", utc(2026, 3, 14, 9, 0) + chrono::TimeDelta::seconds(1)),
+            ("09:00", utc(2026, 3, 15, 9, 0)),
+            ("08:00", utc(2026, 3, 15, 8, 0)),
+            ("today 23:59", utc(2026, 3, 14, 23, 59)),
+            ("tomorrow", utc(2026, 3, 15, 0, 0)),
+            ("tomorrow 08:00", utc(2026, 3, 15, 8, 0)),
+        ] {
+            assert_eq!(target(input, local), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn weekdays_and_dates() {
+        let local = LocalZone::Fixed(Tz::UTC);
+        for (input, expected) in [
+            ("monday", utc(2026, 3, 16, 0, 0)),
+            ("Fri 18:00", utc(2026, 3, 20, 18, 0)),
+            ("saturday 10:00", utc(2026, 3, 14, 10, 0)),
+            ("saturday 08:00", utc(2026, 3, 21, 8, 0)),
+            ("next saturday 10:00", utc(2026, 3, 21, 10, 0)),
+            ("next monday 09:00", utc(2026, 3, 16, 9, 0)),
+            ("2026-12-31", utc(2026, 12, 31, 0, 0)),
+            ("2026-12-31 23:59", utc(2026, 12, 31, 23, 59)),
+            ("2026-12-31T23:59", utc(2026, 12, 31, 23, 59)),
+        ] {
+            assert_eq!(target(input, local), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn named_and_local_time_zones() {
+        // New York is on daylight saving time from 2026-03-08, four hours behind UTC
+        let new_york = parse_target("17:30 America/New_York", now(), LocalZone::Fixed(Tz::UTC)).unwrap();
+        assert_eq!(new_york.at, utc(2026, 3, 14, 21, 30));
+        assert_eq!(new_york.zone, Some(America::New_York));
+        assert_eq!(new_york.display(LocalZone::Fixed(Tz::UTC)), "Sat 14 Mar 2026 17:30:00 EDT");
+
+        let berlin = LocalZone::Fixed(Europe::Berlin);
+        assert_eq!(target("17:30", berlin), utc(2026, 3, 14, 16, 30));
+        // 09:30 in Berlin has passed already at 09:00 UTC
+        assert_eq!(target("09:30", berlin), utc(2026, 3, 15, 8, 30));
+        let local = parse_target("17:30", now(), berlin).unwrap();
+        assert_eq!(local.zone, None);
+        assert_eq!(local.display(berlin), "Sat 14 Mar 2026 17:30:00");
+    }
+
+    #[test]
+    fn daylight_saving_gaps_and_overlaps() {
+        let berlin = LocalZone::Fixed(Europe::Berlin);
+        // the clocks skip from 02:00 to 03:00 on 2026-03-29
+        assert_eq!(
+            parse_target("2026-03-29 02:30", now(), berlin).unwrap_err().to_string(),
+            "2026-03-29 02:30:00 does not exist in that time zone, the clocks skip it"
+        );
+        assert_eq!(target("2026-03-29 03:00", berlin), utc(2026, 3, 29, 1, 0));
+
+        // and go back from 03:00 to 02:00 on 2026-10-25, so 02:30 comes twice
+        assert_eq!(target("2026-10-25 02:30", berlin), utc(2026, 10, 25, 0, 30));
+        assert_eq!(target("2026-10-25 02:30 Europe/Berlin", LocalZone::Fixed(Tz::UTC)), utc(2026, 10, 25, 0, 30));
+    }
+
+    #[test]
+    fn errors() {
+        for (input, expected) in [
+            ("", "missing date or time"),
+            ("until", "missing date or time"),
+            ("today 08:00", "2026-03-14 08:00:00 is in the past"),
+            ("2026-01-01", "2026-01-01 00:00:00 is in the past"),
+            ("next", "expected a weekday after 'next'"),
+            ("next 17:30", "expected a weekday after 'next'"),
+            ("today tomorrow", "more than one day given"),
+            ("17:30 18:00", "more than one time given"),
+            ("25:00", "invalid time '25:00', expected hh:mm or hh:mm:ss"),
+            ("Europe/Berlin 17:30", "the time zone 'Europe/Berlin' must come last"),
+            ("17:30 Mars/Olympus", "unknown time zone 'Mars/Olympus', expected a name such as Europe/Berlin"),
+            ("soon", "unexpected 'soon', expected a date, a weekday or a time such as 17:30"),
+        ] {
+            assert_eq!(error(input), expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn only_times_with_a_day_or_keyword_are_targets() {
+        for input in ["until 17:30", "at 9:00", "tomorrow", "next monday", "Sat 10:00", "2026-12-31"] {
+            assert!(is_target(input), "{input}");
+        }
+        for input in ["17:30", "25m", "PT1H", "", "1:30:00"] {
+            assert!(!is_target(input), "{input}");
+        }
+    }
+}
@@ -1,21 +1,45 @@
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::SharedClock;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Timer {
     pub start: Option<Instant>,
     pub elapsed: Duration,
+    clock: SharedClock,
+}
+
+/// A `Timer` saved to disk. `Instant`s do not survive a restart, so a running timer is
+/// anchored to the wall-clock time at which it was saved.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimerSnapshot {
+    pub elapsed: Duration,
+    pub running: bool,
+    pub saved_at: SystemTime,
 }
 
 impl Timer {
     pub fn new() -> Timer {
+        Timer::with_clock(SharedClock::default())
+    }
+
+    pub fn with_clock(clock: SharedClock) -> Timer {
         Timer {
             start: None,
             elapsed: Duration::new(0, 0),
+            clock,
         }
     }
 
+    /// The clock the timer runs on, for tabs that also need the time of day.
+    pub fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
     pub fn start(&mut self) {
-        self.start = Some(Instant::now());
+        self.start = Some(self.clock.now());
     }
 
     pub fn pause(&mut self) {
@@ -30,13 +54,48 @@ impl Timer {
 
     pub fn elapsed(&self) -> Duration {
         match self.start {
-			Some(t1) => {
-				return t1.elapsed() + self.elapsed;
-			},
-			None => {
-				return self.elapsed;
-			},
-		}
+            Some(t1) => self.clock.now().saturating_duration_since(t1) + self.elapsed,
+            None => self.elapsed,
+        }
+    }
+
+    /// Moves the timer to `elapsed` without pausing it.
+    pub fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+        if self.is_running() {
+            self.start();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.start.is_some()
+    }
+
+    pub fn snapshot(&self) -> TimerSnapshot {
+        TimerSnapshot {
+            elapsed: self.elapsed(),
+            running: self.is_running(),
+            saved_at: self.clock.utc().into(),
+        }
+    }
+
+    /// Rebuilds a timer on `clock` from a snapshot. A running timer also counts the time
+    /// that passed since the snapshot was taken.
+    pub fn from_snapshot(snapshot: TimerSnapshot, clock: SharedClock) -> Timer {
+        let mut timer = Timer {
+            start: None,
+            elapsed: snapshot.elapsed,
+            clock,
+        };
+
+        if snapshot.running {
+            // a clock that went backwards counts as no time passed
+            timer.elapsed += SystemTime::from(timer.clock.utc())
+                .duration_since(snapshot.saved_at)
+                .unwrap_or_default();
+            timer.start();
+        }
+        timer
     }
 }
 
@@ -45,3 +104,77 @@ impl Default for Timer {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+
+    fn fake_timer() -> (std::sync::Arc<FakeClock>, Timer) {
+        let fake = FakeClock::new();
+        let timer = Timer::with_clock(SharedClock::new(fake.clone()));
+        (fake, timer)
+    }
+
+    #[test]
+    fn counts_only_while_running() {
+        let (clock, mut timer) = fake_timer();
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(timer.elapsed(), Duration::ZERO);
+
+        timer.start();
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(timer.elapsed(), Duration::from_secs(3));
+
+        timer.pause();
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(timer.elapsed(), Duration::from_secs(3));
+
+        timer.start();
+        clock.advance(Duration::from_millis(1500));
+        assert_eq!(timer.elapsed(), Duration::from_millis(4500));
+    }
+
+    #[test]
+    fn reset_stops_and_clears() {
+        let (clock, mut timer) = fake_timer();
+        timer.start();
+        clock.advance(Duration::from_secs(7));
+
+        timer.reset();
+        clock.advance(Duration::from_secs(7));
+        assert!(!timer.is_running());
+        assert_eq!(timer.elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn set_elapsed_keeps_running() {
+        let (clock, mut timer) = fake_timer();
+        timer.start();
+        clock.advance(Duration::from_secs(2));
+
+        timer.set_elapsed(Duration::from_secs(60));
+        clock.advance(Duration::from_secs(1));
+        assert!(timer.is_running());
+        assert_eq!(timer.elapsed(), Duration::from_secs(61));
+    }
+
+    #[test]
+    fn snapshot_of_running_timer_counts_time_since_saving() {
+        let (clock, mut timer) = fake_timer();
+        timer.start();
+        clock.advance(Duration::from_secs(30));
+        let snapshot = timer.snapshot();
+
+        clock.advance(Duration::from_secs(90));
+        let restored = Timer::from_snapshot(snapshot, timer.clock().clone());
+        assert!(restored.is_running());
+        assert_eq!(restored.elapsed(), Duration::from_secs(120));
+
+        timer.pause();
+        let restored = Timer::from_snapshot(timer.snapshot(), timer.clock().clone());
+        clock.advance(Duration::from_secs(90));
+        assert!(!restored.is_running());
+        assert_eq!(restored.elapsed(), Duration::from_secs(120));
+    }
+}
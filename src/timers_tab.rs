@@ -0,0 +1,389 @@
+use std::time::Duration;
+
+use anyhow::{Ok, Result};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::clock::SharedClock;
+use crate::colors::theme;
+use crate::config::Config;
+use crate::countdown_tab::CountdownTab;
+use crate::history::SessionRecord;
+use crate::hooks::HookEvent;
+use crate::keymap::TextInput;
+use crate::msg::Message;
+use crate::tabs::{AppTab, TabStatus};
+
+
+/// Longest label that can be typed, so the list stays compact.
+const MAX_LABEL_LEN: usize = 24;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedTimers {
+    timers: Vec<Value>,
+    selected: usize,
+}
+
+/// A label being typed, for a new timer or to rename the selected one.
+#[derive(Debug, Clone, PartialEq)]
+struct LabelInput {
+    text: String,
+    renaming: bool,
+}
+
+/// Any number of labelled countdowns running side by side. The selected one is shown
+/// in full, the others in a compact list.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TimersTab {
+    timers: Vec<CountdownTab>,
+    selected: usize,
+    label_input: Option<LabelInput>,
+    /// Applied to every timer, including ones created later
+    config: Config,
+    /// Records of removed timers, until they are taken
+    removed_records: Vec<SessionRecord>,
+    clock: SharedClock,
+}
+
+impl TimersTab {
+
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            clock,
+            ..Self::default()
+        }
+    }
+
+    fn selected_timer(&mut self) -> Option<&mut CountdownTab> {
+        self.timers.get_mut(self.selected)
+    }
+
+    fn new_timer(&mut self) {
+        self.label_input = Some(LabelInput {
+            text: String::new(),
+            renaming: false,
+        });
+    }
+
+    fn rename(&mut self) {
+        if let Some(timer) = self.timers.get(self.selected) {
+            self.label_input = Some(LabelInput {
+                text: timer.label().to_string(),
+                renaming: true,
+            });
+        }
+    }
+
+    /// Finishes typing a label. A new timer starts out waiting for its duration.
+    fn confirm_label(&mut self) {
+        let Some(input) = self.label_input.take() else {
+            return;
+        };
+
+        let label = match input.text.trim() {
+            "" => format!("Timer {}", self.timers.len() + 1),
+            label => label.to_string(),
+        };
+
+        if input.renaming {
+            if let Some(timer) = self.selected_timer() {
+                timer.set_label(label);
+            }
+        } else {
+            self.timers.push(CountdownTab::new(label, &self.config, self.clock.clone()));
+            self.selected = self.timers.len() - 1;
+        }
+    }
+
+    fn label_char(&mut self, c: char) {
+        if let Some(input) = self.label_input.as_mut() {
+            if !c.is_control() && input.text.chars().count() < MAX_LABEL_LEN {
+                input.text.push(c);
+            }
+        }
+    }
+
+    fn label_backspace(&mut self) {
+        if let Some(input) = self.label_input.as_mut() {
+            input.text.pop();
+        }
+    }
+
+    fn remove(&mut self) {
+        if self.selected >= self.timers.len() {
+            return;
+        }
+
+        // a timer removed mid-run still shows up in the history as cancelled
+        let mut timer = self.timers.remove(self.selected);
+        timer.discard();
+        self.removed_records.extend(timer.take_records());
+
+        self.selected = self.selected.min(self.timers.len().saturating_sub(1));
+    }
+
+    /// Esc while a timer waits for its duration throws it away, it has no time set anyway.
+    fn cancel(&mut self) {
+        if self.timers.get(self.selected).is_some_and(|timer| timer.state().is_setup()) {
+            self.remove();
+        }
+    }
+
+    fn select(&mut self, forward: bool) {
+        let count = self.timers.len();
+        if count == 0 {
+            return;
+        }
+
+        self.selected = if forward {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+    }
+
+    fn timers_table(&self) -> Table<'_> {
+        let rows = self.timers.iter().map(|timer| {
+            let state = if timer.state().is_setup() {
+                "setup".dim()
+            } else if timer.overtime().is_some() {
+                "over".fg(theme().overtime)
+            } else if timer.state().is_running() {
+                "running".fg(theme().running)
+            } else if timer.is_finished() {
+                "done".dim()
+            } else {
+                "paused".fg(theme().stopped)
+            };
+
+            Row::new(vec![
+                Cell::from(timer.label()),
+                Cell::from(timer.time_text()),
+                Cell::from(state),
+            ])
+        });
+
+        let widths = [
+            Constraint::Min(10),
+            Constraint::Length(16),
+            Constraint::Length(7),
+        ];
+
+        Table::new(rows, widths)
+            .column_spacing(2)
+            .highlight_style(Style::new().reversed())
+            .block(Block::default().borders(Borders::RIGHT).dim())
+            .bg(theme().main_background)
+    }
+
+    fn title_paragraph(&self) -> Paragraph<'_> {
+        let title = match (&self.label_input, self.timers.get(self.selected)) {
+            (Some(input), _) => Line::from(vec![
+                "Label: ".dim(),
+                input.text.as_str().into(),
+                " ".reversed(),
+            ]),
+            (None, Some(timer)) => Line::from(timer.label().bold()),
+            (None, None) => Line::from("No timers yet".dim()),
+        };
+
+        Paragraph::new(title).centered()
+    }
+
+}
+
+pub fn layout(area: Rect) -> Vec<Rect> {
+    let columns = Layout::horizontal(vec![
+        Constraint::Length(46), // list
+        Constraint::Min(0), // selected timer
+    ])
+    .split(area);
+
+    let list = Layout::vertical(vec![
+        Constraint::Length(1), // SPACE
+        Constraint::Min(0), // timers
+    ])
+    .split(columns[0]);
+
+    let selected = Layout::vertical(vec![
+        Constraint::Percentage(50), // INGORE
+        Constraint::Length(1), // label
+        Constraint::Length(10), // countdown
+        Constraint::Percentage(50), // INGORE
+    ])
+    .split(columns[1]);
+
+    vec![list[1], selected[1], selected[2]]
+}
+
+impl AppTab for TimersTab {
+    fn id(&self) -> &'static str {
+        "timers"
+    }
+
+    fn title(&self) -> &'static str {
+        "Timers"
+    }
+
+    fn icon(&self) -> &'static str {
+        "📋"
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        if self.label_input.is_some() {
+            match message {
+                Message::Input(c) => self.label_char(c),
+                Message::Backspace => self.label_backspace(),
+                Message::ToggleStartPause => self.confirm_label(),
+                Message::Cancel => self.label_input = None,
+                _ => (),
+            }
+            return;
+        }
+
+        match message {
+            Message::New => self.new_timer(),
+            Message::Rename => self.rename(),
+            Message::Remove => self.remove(),
+            Message::Cancel => self.cancel(),
+            Message::ScrollUp => self.select(false),
+            Message::ScrollDown => self.select(true),
+            _ => {
+                if let Some(timer) = self.selected_timer() {
+                    timer.handle_message(message);
+                }
+            }
+        }
+    }
+
+    fn tick(&mut self) {
+        for timer in self.timers.iter_mut() {
+            timer.tick();
+        }
+    }
+
+    /// The list shows every timer, so the one that changes soonest counts.
+    fn redraw_in(&self) -> Option<Duration> {
+        self.timers.iter().filter_map(CountdownTab::redraw_in).min()
+    }
+
+    fn apply_config(&mut self, config: &Config) {
+        self.config = config.clone();
+        for timer in self.timers.iter_mut() {
+            timer.apply_config(config);
+        }
+    }
+
+    fn render_paragraph(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
+        let area = layout(*area);
+
+        let mut table_state = TableState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(self.timers_table(), area[0], &mut table_state);
+        frame.render_widget(self.title_paragraph(), area[1]);
+
+        if self.label_input.is_none() {
+            if let Some(timer) = self.timers.get_mut(self.selected) {
+                timer.render_paragraph(frame, &area[2])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The running timer that ends first, or the selected one when none is running.
+    fn status(&self) -> Option<TabStatus> {
+        self.timers
+            .iter()
+            .filter(|timer| timer.state().is_running())
+            .min_by_key(|timer| timer.remaining())
+            .or(self.timers.get(self.selected))
+            .and_then(CountdownTab::status)
+    }
+
+    fn save_state(&self) -> Option<Value> {
+        let saved = SavedTimers {
+            timers: self.timers.iter().filter_map(CountdownTab::save_state).collect(),
+            selected: self.selected,
+        };
+        serde_json::to_value(saved).ok()
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<Option<String>> {
+        let saved: SavedTimers = serde_json::from_value(state)?;
+
+        let mut notices = vec![];
+        self.timers.clear();
+        for state in saved.timers {
+            let mut timer = CountdownTab::new(String::new(), &self.config, self.clock.clone());
+            if let Some(notice) = timer.restore_state(state)? {
+                notices.push(notice);
+            }
+            self.timers.push(timer);
+        }
+        self.selected = saved.selected.min(self.timers.len().saturating_sub(1));
+
+        Ok((!notices.is_empty()).then(|| notices.join(" | ")))
+    }
+
+    fn help_entries(&self) -> Vec<(Message, &'static str)> {
+        if self.label_input.is_some() {
+            return vec![
+                (Message::ToggleStartPause, "Confirm"),
+                (Message::Cancel, "Cancel"),
+            ];
+        }
+
+        let mut entries = self.timers
+            .get(self.selected)
+            .map(CountdownTab::help_entries)
+            .unwrap_or_default();
+
+        entries.push((Message::New, "New Timer"));
+
+        if let Some(timer) = self.timers.get(self.selected) {
+            if timer.state().is_setup() {
+                entries.push((Message::Cancel, "Discard"));
+            } else {
+                entries.push((Message::Rename, "Rename"));
+                entries.push((Message::Remove, "Remove"));
+            }
+        }
+
+        if self.timers.len() > 1 {
+            entries.push((Message::ScrollUp, "Select"));
+            entries.push((Message::ScrollDown, "Select"));
+        }
+
+        entries
+    }
+
+    fn take_records(&mut self) -> Vec<SessionRecord> {
+        self.timers
+            .iter_mut()
+            .flat_map(|timer| timer.take_records())
+            .chain(self.removed_records.drain(..))
+            .map(|record| SessionRecord {
+                tab: String::from("timers"),
+                ..record
+            })
+            .collect()
+    }
+
+    fn take_hook_events(&mut self) -> Vec<HookEvent> {
+        self.timers
+            .iter_mut()
+            .flat_map(|timer| timer.take_hook_events())
+            .collect()
+    }
+
+    /// Typing a label or a duration works like a form: every key is text until Enter or Esc.
+    fn text_input(&self) -> TextInput {
+        let selected_in_setup = self.timers.get(self.selected).is_some_and(|timer| timer.state().is_setup());
+        if self.label_input.is_some() || selected_in_setup {
+            TextInput::All
+        } else {
+            TextInput::Off
+        }
+    }
+}
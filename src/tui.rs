@@ -1,22 +1,25 @@
 use std::io::{self, Stdout};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
 
 use anyhow::{Context, Result};
 use ratatui::prelude::*;
-use crossterm::{execute, terminal::{disable_raw_mode, enable_raw_mode, LeaveAlternateScreen, EnterAlternateScreen}};
+use crossterm::{cursor, execute, terminal::{disable_raw_mode, enable_raw_mode, LeaveAlternateScreen, EnterAlternateScreen}};
 
 
+/// Whether the terminal is in raw mode on the alternate screen, so it is restored only once.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
 pub struct Tui {
     terminal: Terminal<CrosstermBackend<Stdout>>,
 }
 
 impl Tui {
     pub fn init() -> Result<Tui> {
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen).context("failed to enter alternate screen")?;
-        let backend = CrosstermBackend::new(stdout);
+        install_panic_hook();
+        enter()?;
+        let backend = CrosstermBackend::new(io::stdout());
         let mut terminal = Terminal::new(backend).context("failed to create terminal")?;
-        enable_raw_mode().context("failed to enable raw mode")?;
-        terminal.hide_cursor().context("failed to hide cursor")?;
         terminal.clear().context("failed to clear console")?;
         Ok(Self { terminal })
     }
@@ -25,14 +28,67 @@ impl Tui {
         self.terminal.draw(frame).context("failed to draw frame")?;
         Ok(())
     }
+
+    /// Hands the terminal back to the shell and stops the process until it is continued,
+    /// e.g. with `fg`, then takes the terminal over again.
+    #[cfg(unix)]
+    pub fn suspend(&mut self) -> Result<()> {
+        restore()?;
+        // SIGTSTP is caught by the app instead of stopping it, SIGSTOP cannot be caught
+        // SAFETY: raise only sends a signal to the calling process
+        unsafe {
+            libc::raise(libc::SIGSTOP);
+        }
+        enter()?;
+        // the shell drew over the screen meanwhile, so the next frame is drawn in full
+        self.terminal.clear().context("failed to clear console")
+    }
+
+    #[cfg(not(unix))]
+    pub fn suspend(&mut self) -> Result<()> {
+        Ok(())
+    }
 }
 
 impl Drop for Tui {
     fn drop(&mut self) {
-        disable_raw_mode().expect("failed to disable raw mode");
-        execute!(self.terminal.backend_mut(), LeaveAlternateScreen)
-            .expect("failed to switch to main screen");
-        self.terminal.show_cursor().expect("failed to show cursor");
-        self.terminal.clear().expect("failed to clear console");
+        // after a panic the terminal is already restored, and its message stays on screen
+        if ACTIVE.load(Ordering::SeqCst) {
+            // the terminal may be gone, e.g. after SIGHUP, and there is no one to tell
+            let _ = restore();
+            let _ = self.terminal.clear();
+        }
+    }
+}
+
+/// Switches to raw mode on the alternate screen.
+fn enter() -> Result<()> {
+    execute!(io::stdout(), EnterAlternateScreen, cursor::Hide).context("failed to enter alternate screen")?;
+    enable_raw_mode().context("failed to enable raw mode")?;
+    ACTIVE.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Leaves raw mode and the alternate screen and shows the cursor, if `enter` did not
+/// already get undone.
+fn restore() -> Result<()> {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
     }
-}
\ No newline at end of file
+    disable_raw_mode().context("failed to disable raw mode")?;
+    execute!(io::stdout(), LeaveAlternateScreen, cursor::Show).context("failed to switch to main screen")?;
+    Ok(())
+}
+
+/// Restores the terminal before a panic is reported, so the message is not lost on the
+/// alternate screen and the shell is not left in raw mode.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let report = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = restore();
+            report(info);
+        }));
+    });
+}
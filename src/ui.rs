@@ -1,10 +1,31 @@
-use std::time::Duration;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
 
 
 use ratatui::prelude::*;
+use serde::Deserialize;
 use tui_big_text::{BigText, PixelSize};
 
-use crate::{colors::BG_MAIN_COLOR, states::AppState};
+use crate::{colors::theme, states::AppState};
+
+static TIME_FORMAT: RwLock<TimeFormat> = RwLock::new(TimeFormat::Millis);
+
+/// How precisely `format_duration` prints the fractional part of a second.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeFormat {
+    /// `00:00:00.000`
+    #[default]
+    Millis,
+    /// `00:00:00.00`
+    Centis,
+    /// `00:00:00`
+    Seconds,
+}
+
+pub fn set_time_format(format: TimeFormat) {
+    *TIME_FORMAT.write().unwrap_or_else(|err| err.into_inner()) = format;
+}
 
 pub fn layout(area: Rect) -> Vec<Rect> {
     let layout = Layout::vertical(vec![
@@ -15,7 +36,7 @@ pub fn layout(area: Rect) -> Vec<Rect> {
     .split(area);
     let top_layout = Layout::horizontal(vec![
         Constraint::Length(15), // title
-        Constraint::Ratio(1, 2), // tabs
+        Constraint::Min(0), // tabs
         Constraint::Length(15), // fps counter
     ])
     .flex(layout::Flex::SpaceBetween)
@@ -29,39 +50,135 @@ pub fn layout(area: Rect) -> Vec<Rect> {
         .collect()
 }
 
+/// Formats a duration as `hh:mm:ss` with the configured fraction of a second,
+/// prefixed by the number of days once it reaches a day, e.g. `3d 04:05:06.000`.
 pub fn format_duration(duration: Duration) -> String {
-    format!(
-        "{:02}:{:02}:{:02}.{:03}",
-        duration.as_secs() / 3600,
-        (duration.as_secs() / 60) % 60,
-        duration.as_secs() % 60,
-        duration.as_millis() % 1000
-    )
+    let time_format = *TIME_FORMAT.read().unwrap_or_else(|err| err.into_inner());
+    let secs = duration.as_secs();
+    let days = match secs / 86400 {
+        0 => String::new(),
+        days => format!("{days}d "),
+    };
+    let clock = format!(
+        "{}{:02}:{:02}:{:02}",
+        days,
+        (secs / 3600) % 24,
+        (secs / 60) % 60,
+        secs % 60,
+    );
+
+    match time_format {
+        TimeFormat::Millis => format!("{}.{:03}", clock, duration.subsec_millis()),
+        TimeFormat::Centis => format!("{}.{:02}", clock, duration.subsec_millis() / 10),
+        TimeFormat::Seconds => clock,
+    }
+}
+
+/// Time until `format_duration` shows something else for a running timer that stands at
+/// `duration`, counting up or down. Zero while fractions of a second are shown, as the
+/// timer then changes on every frame.
+pub fn next_change(duration: Duration, counting_down: bool) -> Duration {
+    let time_format = *TIME_FORMAT.read().unwrap_or_else(|err| err.into_inner());
+    if time_format != TimeFormat::Seconds {
+        return Duration::ZERO;
+    }
+
+    let fraction = Duration::from_nanos(duration.subsec_nanos().into());
+    if counting_down {
+        fraction
+    } else {
+        Duration::from_secs(1) - fraction
+    }
+}
+
+/// Formats a signed number of seconds compactly, e.g. `+1m`, `-10s` or `+1h30m`.
+pub fn format_adjustment(seconds: i64) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.unsigned_abs();
+    let parts = [(seconds / 3600, "h"), ((seconds / 60) % 60, "m"), (seconds % 60, "s")];
+
+    let text: String = parts
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .collect();
+    if text.is_empty() { format!("{sign}0s") } else { format!("{sign}{text}") }
+}
+
+/// Short-lived feedback shown under a timer, e.g. `+1m` after adjusting it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flash {
+    text: String,
+    shown_at: Instant,
+}
+
+impl Flash {
+    const DURATION: Duration = Duration::from_millis(1500);
+
+    /// A flash shown from `now`, the monotonic time of the tab's clock.
+    pub fn new(text: String, now: Instant) -> Self {
+        Self {
+            text,
+            shown_at: now,
+        }
+    }
+
+    /// The text while it should still be shown.
+    pub fn text(&self, now: Instant) -> Option<&str> {
+        self.remaining(now).map(|_| self.text.as_str())
+    }
+
+    /// Time until the flash disappears, `None` once it has.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        Self::DURATION
+            .checked_sub(now.saturating_duration_since(self.shown_at))
+            .filter(|remaining| !remaining.is_zero())
+    }
+}
+
+/// The sooner of two redraws, where `None` means never.
+pub fn sooner(a: Option<Duration>, b: Option<Duration>) -> Option<Duration> {
+    a.into_iter().chain(b).min()
+}
+
+/// The text of a flash that is still showing, for a line under a timer.
+pub fn flash_line(flash: &Option<Flash>, now: Instant) -> Line<'_> {
+    flash
+        .as_ref()
+        .and_then(|flash| flash.text(now))
+        .map(|text| Line::from(text.bold()))
+        .unwrap_or_default()
 }
 
 pub fn timer_style(state: AppState) -> Style {
+    let theme = theme();
     if state.is_running() {
-        Style::new().green().bg(BG_MAIN_COLOR)
+        Style::new().fg(theme.running).bg(theme.main_background)
     } else {
-        Style::new().red()
+        Style::new().fg(theme.stopped)
     }
 }
 
 pub fn setup_big_timer_text(duration: String, state: AppState, width: u16) -> BigText<'static> {
+    setup_big_text(duration, timer_style(state), width)
+}
+
+/// Big centered text, larger on wide terminals.
+pub fn setup_big_text(text: String, style: Style, width: u16) -> BigText<'static> {
     let pixel_size = match width {
         150.. => PixelSize::HalfHeight,
         _ => PixelSize::Sextant
     };
 
-    let lines = vec![duration.into()];
+    let lines = vec![text.into()];
         BigText::builder()
             // HalfHeight - h1 style
             // Quadrant - h2 style
             // Sextant - h3 style
             .pixel_size(pixel_size)
             .lines(lines)
-            .style(timer_style(state))
+            .style(style)
             .alignment(Alignment::Center)
             .build()
-            .unwrap()
+            .expect("the lines, the only field without a default, are set")
 }
\ No newline at end of file
@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::clock::SharedClock;
+
+
+/// Notices when a file changes on disk so it can be reloaded.
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    path: PathBuf,
+    /// Modification time and size, so appends within the same second are noticed too
+    stamp: Option<(SystemTime, u64)>,
+    last_check: Instant,
+    clock: SharedClock,
+}
+
+impl FileWatcher {
+    const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new(path: PathBuf, clock: SharedClock) -> Self {
+        let stamp = file_stamp(&path);
+        Self {
+            path,
+            stamp,
+            last_check: clock.now(),
+            clock,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true once per change of the file, checking at most once a second.
+    pub fn changed(&mut self) -> bool {
+        let now = self.clock.now();
+        if now.saturating_duration_since(self.last_check) < Self::CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = now;
+
+        let stamp = file_stamp(&self.path);
+        if stamp == self.stamp {
+            return false;
+        }
+        self.stamp = stamp;
+        true
+    }
+
+    /// Takes the file as it is now as unchanged, after writing it ourselves.
+    pub fn written(&mut self) {
+        self.stamp = file_stamp(&self.path);
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+
+    #[test]
+    fn checks_at_most_once_a_second() {
+        let dir = std::env::temp_dir().join("timemann-watcher").join(format!("{}-checks", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(&path, "fps = 30\n").unwrap();
+
+        let clock = FakeClock::new();
+        let mut watcher = FileWatcher::new(path.clone(), SharedClock::new(clock.clone()));
+        fs::write(&path, "fps = 60.0\n").unwrap();
+        clock.advance(Duration::from_millis(999));
+        assert!(!watcher.changed());
+
+        clock.advance(Duration::from_millis(1));
+        assert!(watcher.changed());
+        clock.advance(Duration::from_secs(1));
+        assert!(!watcher.changed(), "noticed once");
+
+        fs::remove_file(&path).unwrap();
+        assert!(!watcher.changed(), "checked again right away");
+        clock.advance(Duration::from_secs(1));
+        assert!(watcher.changed(), "removed");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn own_writes_are_not_changes_but_later_ones_are() {
+        let dir = std::env::temp_dir().join("timemann-watcher").join(format!("{}-written", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history.jsonl");
+        fs::write(&path, "{}\n{}\n").unwrap();
+
+        let clock = FakeClock::new();
+        let mut watcher = FileWatcher::new(path.clone(), SharedClock::new(clock.clone()));
+        fs::write(&path, "{}\n").unwrap();
+        watcher.written();
+        clock.advance(Duration::from_secs(1));
+        assert!(!watcher.changed());
+
+        fs::write(&path, "{}\n{}\n").unwrap();
+        assert!(!watcher.changed(), "checked within a second");
+        clock.advance(Duration::from_secs(1));
+        assert!(watcher.changed());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,308 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::config::describe_toml_error;
+use crate::duration::parse_duration;
+
+
+/// Upper bound on unrolled intervals, so a typo like `repeat = 80000000` fails cleanly.
+const MAX_INTERVALS: usize = 10_000;
+
+/// Built-in workout shown when no workout files exist yet.
+const DEFAULT_WORKOUT: &str = r#"
+name = "Tabata"
+
+[[steps]]
+label = "Warmup"
+duration = "5m"
+
+[[steps]]
+repeat = 8
+steps = [
+    { label = "Work", duration = "20s" },
+    { label = "Rest", duration = "10s" },
+]
+
+[[steps]]
+label = "Cooldown"
+duration = "3m"
+"#;
+
+/// A workout as written in a `.toml` file: a name and a list of steps,
+/// where a step is either timed or repeats its own list of steps.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkoutFile {
+    pub name: String,
+    pub steps: Vec<Step>,
+}
+
+/// A timed step has a `label` and a `duration`, a repeating one has `repeat` and `steps`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    pub label: Option<String>,
+    pub duration: Option<String>,
+    pub repeat: Option<u32>,
+    #[serde(default)]
+    pub steps: Vec<Step>,
+}
+
+/// One timed step of a flattened workout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub label: String,
+    pub duration: Duration,
+    /// Current and total round of the innermost repeat this step belongs to
+    pub round: Option<(u32, u32)>,
+}
+
+/// A workout with its repeats unrolled into a flat list of intervals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workout {
+    pub name: String,
+    pub intervals: Vec<Interval>,
+}
+
+impl Workout {
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: WorkoutFile = toml::from_str(text).map_err(|err| describe_toml_error(text, err))?;
+        Self::from_file(file)
+    }
+
+    pub fn from_file(file: WorkoutFile) -> Result<Self> {
+        if file.name.trim().is_empty() {
+            bail!("workout name is empty");
+        }
+
+        let mut intervals = vec![];
+        flatten(&file.steps, None, &mut intervals)?;
+        if intervals.is_empty() {
+            bail!("workout has no steps");
+        }
+
+        Ok(Self {
+            name: file.name,
+            intervals,
+        })
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.intervals.iter().map(|interval| interval.duration).sum()
+    }
+
+    /// The interval running after `elapsed`, with the time at which it started.
+    pub fn interval_at(&self, elapsed: Duration) -> Option<(usize, Duration)> {
+        let mut start = Duration::ZERO;
+        for (index, interval) in self.intervals.iter().enumerate() {
+            if elapsed < start + interval.duration {
+                return Some((index, start));
+            }
+            start += interval.duration;
+        }
+        None
+    }
+
+    /// Time at which the interval at `index` starts.
+    pub fn start_of(&self, index: usize) -> Duration {
+        self.intervals.iter().take(index).map(|interval| interval.duration).sum()
+    }
+}
+
+impl Default for Workout {
+    fn default() -> Self {
+        Self::parse(DEFAULT_WORKOUT).expect("built-in workout is valid")
+    }
+}
+
+fn flatten(steps: &[Step], round: Option<(u32, u32)>, out: &mut Vec<Interval>) -> Result<()> {
+    for step in steps {
+        let name = step.label.as_deref().unwrap_or("unnamed");
+        match (&step.duration, step.repeat) {
+            (Some(duration), None) => {
+                if !step.steps.is_empty() {
+                    bail!("step '{name}' has a duration, so it cannot have steps of its own");
+                }
+                let duration = parse_duration(duration)
+                    .with_context(|| format!("invalid duration in step '{name}'"))?;
+                if duration.is_zero() {
+                    bail!("step '{name}' must be longer than zero");
+                }
+                out.push(Interval {
+                    label: step.label.clone().unwrap_or_default(),
+                    duration,
+                    round,
+                });
+            }
+            (None, Some(repeat)) => {
+                if repeat == 0 {
+                    bail!("repeat must be at least 1");
+                }
+                if step.steps.is_empty() {
+                    bail!("repeat has no steps");
+                }
+                for current in 1..=repeat {
+                    flatten(&step.steps, Some((current, repeat)), out)?;
+                    if out.len() > MAX_INTERVALS {
+                        bail!("workout has more than {MAX_INTERVALS} steps once repeats are unrolled");
+                    }
+                }
+            }
+            (Some(_), Some(_)) => bail!("step '{name}' has both a duration and a repeat"),
+            (None, None) => bail!("step '{name}' needs either a duration or a repeat"),
+        }
+    }
+    Ok(())
+}
+
+/// Directory holding the user's workout files, `$XDG_CONFIG_HOME/timemann/workouts`.
+pub fn workouts_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("timemann").join("workouts"))
+}
+
+pub fn load_workout(path: &Path) -> Result<Workout> {
+    let text = fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Workout::parse(&text).with_context(|| format!("invalid workout file {}", path.display()))
+}
+
+/// Loads every `.toml` file in `dir`, sorted by file name. Files that fail to load
+/// are returned as errors next to the workouts that did.
+pub fn load_workouts(dir: &Path) -> (Vec<Workout>, Vec<String>) {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect(),
+        Err(_) => vec![],
+    };
+    paths.sort();
+
+    let mut workouts = vec![];
+    let mut errors = vec![];
+    for path in paths {
+        match load_workout(&path) {
+            Ok(workout) => workouts.push(workout),
+            Err(err) => errors.push(format!("{err:#}")),
+        }
+    }
+    (workouts, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn intervals(text: &str) -> Vec<Interval> {
+        Workout::parse(text).unwrap_or_else(|err| panic!("{text}: {err:#}")).intervals
+    }
+
+    fn interval(label: &str, duration: u64, round: Option<(u32, u32)>) -> Interval {
+        Interval { label: label.to_string(), duration: secs(duration), round }
+    }
+
+    fn error(text: &str) -> String {
+        format!("{:#}", Workout::parse(text).unwrap_err())
+    }
+
+    #[test]
+    fn flattens_steps_and_repeats() {
+        for (text, expected) in [
+            (
+                "name = 'Run'\nsteps = [{ label = 'Go', duration = '30m' }]",
+                vec![interval("Go", 1800, None)],
+            ),
+            (
+                "name = 'Unlabelled'\nsteps = [{ duration = '1m' }]",
+                vec![interval("", 60, None)],
+            ),
+            (
+                "name = 'Sprints'\nsteps = [
+                    { label = 'Warmup', duration = '5m' },
+                    { repeat = 2, steps = [{ label = 'Sprint', duration = '30s' }, { label = 'Walk', duration = '1m' }] },
+                ]",
+                vec![
+                    interval("Warmup", 300, None),
+                    interval("Sprint", 30, Some((1, 2))),
+                    interval("Walk", 60, Some((1, 2))),
+                    interval("Sprint", 30, Some((2, 2))),
+                    interval("Walk", 60, Some((2, 2))),
+                ],
+            ),
+            // a nested repeat counts its own rounds, the steps around it those of the outer one
+            (
+                "name = 'Ladder'\nsteps = [{ repeat = 2, steps = [
+                    { repeat = 2, steps = [{ label = 'Lift', duration = '40s' }] },
+                    { label = 'Rest', duration = '2m' },
+                ] }]",
+                vec![
+                    interval("Lift", 40, Some((1, 2))),
+                    interval("Lift", 40, Some((2, 2))),
+                    interval("Rest", 120, Some((1, 2))),
+                    interval("Lift", 40, Some((1, 2))),
+                    interval("Lift", 40, Some((2, 2))),
+                    interval("Rest", 120, Some((2, 2))),
+                ],
+            ),
+        ] {
+            assert_eq!(intervals(text), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn default_workout_is_a_tabata() {
+        let workout = Workout::default();
+        assert_eq!(workout.name, "Tabata");
+        assert_eq!(workout.intervals.len(), 18);
+        assert_eq!(workout.total_duration(), secs(5 * 60 + 8 * 30 + 3 * 60));
+        assert_eq!(workout.interval_at(secs(0)), Some((0, secs(0))));
+        assert_eq!(workout.interval_at(secs(5 * 60 + 25)), Some((2, secs(5 * 60 + 20))));
+        assert_eq!(workout.interval_at(workout.total_duration()), None);
+        assert_eq!(workout.start_of(17), secs(5 * 60 + 8 * 30));
+    }
+
+    #[test]
+    fn caps_unrolled_repeats() {
+        let at_cap = "name = 'Long'\nsteps = [{ repeat = 10000, steps = [{ duration = '1s' }] }]";
+        assert_eq!(intervals(at_cap).len(), MAX_INTERVALS);
+
+        for text in [
+            "name = 'Typo'\nsteps = [{ repeat = 10001, steps = [{ duration = '1s' }] }]",
+            "name = 'Typo'\nsteps = [{ repeat = 80000000, steps = [{ duration = '1s' }] }]",
+            "name = 'Nested'\nsteps = [{ repeat = 1000, steps = [{ repeat = 1000, steps = [{ duration = '1s' }] }] }]",
+        ] {
+            assert_eq!(error(text), "workout has more than 10000 steps once repeats are unrolled", "{text}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_workouts() {
+        for (text, expected) in [
+            ("name = ' '\nsteps = [{ duration = '1m' }]", "workout name is empty"),
+            ("name = 'Empty'\nsteps = []", "workout has no steps"),
+            ("name = 'Zero'\nsteps = [{ repeat = 0, steps = [{ duration = '1m' }] }]", "repeat must be at least 1"),
+            ("name = 'Hollow'\nsteps = [{ repeat = 3 }]", "repeat has no steps"),
+            ("name = 'Instant'\nsteps = [{ label = 'Nap', duration = '0s' }]", "step 'Nap' must be longer than zero"),
+            (
+                "name = 'Both'\nsteps = [{ label = 'Run', duration = '1m', repeat = 2 }]",
+                "step 'Run' has both a duration and a repeat",
+            ),
+            ("name = 'Neither'\nsteps = [{ label = 'Run' }]", "step 'Run' needs either a duration or a repeat"),
+            (
+                "name = 'Nested'\nsteps = [{ label = 'Run', duration = '1m', steps = [{ duration = '1m' }] }]",
+                "step 'Run' has a duration, so it cannot have steps of its own",
+            ),
+        ] {
+            assert_eq!(error(text), expected, "{text}");
+        }
+
+        assert!(error("name = 'Bad'\nsteps = [{ duration = 'soon' }]").starts_with("invalid duration in step 'unnamed'"));
+    }
+}
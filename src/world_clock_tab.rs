@@ -0,0 +1,266 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Offset, Timelike, Utc};
+use chrono_tz::Tz;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
+
+use crate::clock::{LocalZone, SharedClock};
+use crate::colors::theme;
+use crate::config::{ClockView, Config, ZoneConfig};
+use crate::msg::Message;
+use crate::states::AppState;
+use crate::tabs::AppTab;
+use crate::ui::setup_big_timer_text;
+
+
+/// The current time in one configured zone.
+struct ZoneTime {
+    name: String,
+    time: DateTime<Tz>,
+    /// Seconds ahead of UTC
+    offset: i32,
+    /// Seconds ahead of the local time zone
+    ahead_of_local: i32,
+}
+
+impl ZoneTime {
+    fn new(zone: &ZoneConfig, now: DateTime<Utc>, local: LocalZone) -> Self {
+        let time = now.with_timezone(&zone.zone);
+        let offset = time.offset().fix().local_minus_utc();
+        let local_offset = now.with_timezone(&local).offset().fix().local_minus_utc();
+
+        Self {
+            name: zone.display_name(),
+            time,
+            offset,
+            ahead_of_local: offset - local_offset,
+        }
+    }
+
+    /// Daytime is from 6:00 to 18:00 in the zone.
+    fn is_day(&self) -> bool {
+        (6..18).contains(&self.time.hour())
+    }
+
+    fn day_night(&self) -> Span<'static> {
+        if self.is_day() {
+            "☀ day".yellow()
+        } else {
+            "☾ night".blue()
+        }
+    }
+
+    /// e.g. `UTC+05:30`
+    fn offset_text(&self) -> String {
+        format!("UTC{}", signed_hours(self.offset, true))
+    }
+
+    /// e.g. `+9h` or `-5h30m` relative to local time, `local` when the same.
+    fn local_difference_text(&self) -> String {
+        match self.ahead_of_local {
+            0 => String::from("local"),
+            seconds => signed_hours(seconds, false),
+        }
+    }
+}
+
+/// Formats an offset in seconds as `+05:30` when `clock` is set, else as `+5h30m`.
+fn signed_hours(seconds: i32, clock: bool) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.unsigned_abs() / 60;
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+
+    match (clock, minutes) {
+        (true, _) => format!("{sign}{hours:02}:{minutes:02}"),
+        (false, 0) => format!("{sign}{hours}h"),
+        (false, _) => format!("{sign}{hours}h{minutes}m"),
+    }
+}
+
+/// The current time in several named time zones, as a table or in big digits.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WorldClockTab {
+    zones: Vec<ZoneConfig>,
+    view: ClockView,
+    clock: SharedClock,
+}
+
+impl WorldClockTab {
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            clock,
+            ..Self::default()
+        }
+    }
+
+    fn zone_times(&self) -> Vec<ZoneTime> {
+        let now = self.clock.utc();
+        self.zones.iter().map(|zone| ZoneTime::new(zone, now, self.clock.zone())).collect()
+    }
+
+    fn toggle_view(&mut self) {
+        self.view = match self.view {
+            ClockView::Table => ClockView::Big,
+            ClockView::Big => ClockView::Table,
+        };
+    }
+
+    fn zones_table(&self) -> Table<'static> {
+        let rows = self.zone_times().into_iter().map(|zone| {
+            Row::new(vec![
+                Cell::from(zone.name.clone()).bold(),
+                Cell::from(zone.time.format("%H:%M:%S").to_string()),
+                Cell::from(zone.time.format("%a %d %b").to_string()),
+                Cell::from(zone.offset_text()),
+                Cell::from(zone.day_night()),
+                Cell::from(zone.local_difference_text()),
+            ])
+        });
+
+        let widths = [
+            Constraint::Min(12),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(9),
+            Constraint::Length(7),
+            Constraint::Length(7),
+        ];
+
+        Table::new(rows, widths)
+            .header(Row::new(vec!["Zone", "Time", "Date", "Offset", "", "Local"]).bold())
+            .column_spacing(2)
+            .block(Block::default().borders(Borders::TOP).dim())
+            .bg(theme().main_background)
+    }
+
+    fn render_big(&self, frame: &mut Frame, area: Rect) {
+        let zones = self.zone_times();
+        for (zone, area) in zones.iter().zip(big_layout(area, zones.len())) {
+            let area = zone_layout(area);
+
+            frame.render_widget(Paragraph::new(zone.name.as_str()).bold().centered(), area[0]);
+            frame.render_widget(
+                setup_big_timer_text(zone.time.format("%H:%M:%S").to_string(), AppState::Running, area[1].width),
+                area[1],
+            );
+
+            let details = Line::from(vec![
+                zone.time.format("%a %d %b").to_string().into(),
+                "  ".into(),
+                zone.offset_text().into(),
+                "  ".into(),
+                zone.day_night(),
+                "  ".into(),
+                zone.local_difference_text().into(),
+            ]);
+            frame.render_widget(Paragraph::new(details).dim().centered(), area[2]);
+        }
+    }
+
+}
+
+pub fn layout(area: Rect) -> Vec<Rect> {
+    let layout = Layout::vertical(vec![
+        Constraint::Length(1), // INGORE
+        Constraint::Min(0), // clocks
+    ])
+    .split(area);
+
+    let table = Layout::horizontal(vec![
+        Constraint::Percentage(15),
+        Constraint::Min(63), // every column at full width
+        Constraint::Percentage(15),
+    ])
+    .split(layout[1]);
+
+    vec![layout[1], table[1]]
+}
+
+/// Splits the area into a grid with a cell per zone, two columns once there are more than two.
+fn big_layout(area: Rect, count: usize) -> Vec<Rect> {
+    let columns = if count > 2 { 2 } else { 1 };
+    let rows = count.div_ceil(columns).max(1);
+
+    Layout::vertical(vec![Constraint::Ratio(1, rows as u32); rows])
+        .split(area)
+        .iter()
+        .flat_map(|row| {
+            Layout::horizontal(vec![Constraint::Ratio(1, columns as u32); columns])
+                .split(*row)
+                .to_vec()
+        })
+        .collect()
+}
+
+fn zone_layout(area: Rect) -> Vec<Rect> {
+    let layout = Layout::vertical(vec![
+        Constraint::Percentage(50), // INGORE
+        Constraint::Length(1), // name
+        Constraint::Length(4), // time
+        Constraint::Length(1), // details
+        Constraint::Percentage(50), // INGORE
+    ])
+    .split(area);
+
+    layout[1..4].to_vec()
+}
+
+impl AppTab for WorldClockTab {
+    fn id(&self) -> &'static str {
+        "clocks"
+    }
+
+    fn title(&self) -> &'static str {
+        "Clocks"
+    }
+
+    fn icon(&self) -> &'static str {
+        "🌍"
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        if message == Message::ToggleView {
+            self.toggle_view();
+        }
+    }
+
+    fn apply_config(&mut self, config: &Config) {
+        self.zones = config.world_clock.zones.clone();
+        self.view = config.world_clock.view;
+    }
+
+    /// The clocks show seconds, so they change at the start of the next one.
+    fn redraw_in(&self) -> Option<Duration> {
+        if self.zones.is_empty() {
+            return None;
+        }
+        let fraction = Duration::from_nanos(self.clock.utc().timestamp_subsec_nanos().into());
+        Some(Duration::from_secs(1).saturating_sub(fraction))
+    }
+
+    fn render_paragraph(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
+        let area = layout(*area);
+
+        if self.zones.is_empty() {
+            let text = "No time zones configured, add them under [world_clock] in the config";
+            frame.render_widget(Paragraph::new(text).dim().centered(), area[0]);
+            return Ok(());
+        }
+
+        match self.view {
+            ClockView::Table => frame.render_widget(self.zones_table(), area[1]),
+            ClockView::Big => self.render_big(frame, area[0]),
+        }
+        Ok(())
+    }
+
+    fn help_entries(&self) -> Vec<(Message, &'static str)> {
+        let label = match self.view {
+            ClockView::Table => "Big Digits",
+            ClockView::Big => "Table",
+        };
+        vec![(Message::ToggleView, label)]
+    }
+}